@@ -0,0 +1,273 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::primitives::Aabb,
+    utils::{HashMap, HashSet},
+};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    blocks::block_name,
+    components::{
+        self, Block, BlockClicked, BlockStatus, Conveyor, Grabber, Output, Process, Storage,
+    },
+    events::StallReason,
+    fluids::Pipe,
+    grid::BlockGrid,
+    player::{Player, PlayerPluginCamera},
+};
+
+pub struct AlertsPlugin;
+
+impl Plugin for AlertsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Alerts>()
+            .add_system(track_stalls)
+            .add_system(check_alerts)
+            .add_system(alerts_ui);
+    }
+}
+
+/// Seconds a block has to stay stalled before it raises an alert.
+const STALL_SECONDS: f32 = 10.;
+
+const MAX_TOASTS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    Stalled(StallReason),
+    NoReaction,
+    /// Nothing takes what it puts out, or for a grabber nothing on one of its sides. Storage is
+    /// left alone, it is fine for it to be the end of a line.
+    Unconnected,
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub entity: Entity,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+#[derive(Resource)]
+pub struct Alerts {
+    pub alerts: Vec<Alert>,
+    /// How long each stalled block has been stalled for the same reason.
+    stalled_for: HashMap<Entity, (StallReason, f32)>,
+    /// Closed by the player, not raised again until the problem has gone away.
+    dismissed: HashSet<(Entity, AlertKind)>,
+    timer: Timer,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self {
+            alerts: vec![],
+            stalled_for: HashMap::new(),
+            dismissed: HashSet::new(),
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+        }
+    }
+}
+
+fn track_stalls(mut alerts: ResMut<Alerts>, query: Query<(Entity, &BlockStatus)>, time: Res<Time>) {
+    let mut stalled_for = HashMap::new();
+    for (entity, status) in query.iter() {
+        let Some(reason) = status.stalled.clone() else {
+            continue;
+        };
+        let seconds = match alerts.stalled_for.get(&entity) {
+            Some((previous, seconds)) if *previous == reason => *seconds,
+            _ => 0.,
+        };
+        stalled_for.insert(entity, (reason, seconds + time.delta_seconds()));
+    }
+    alerts.stalled_for = stalled_for;
+}
+
+fn stall_message(name: &str, reason: &StallReason) -> String {
+    match reason {
+        StallReason::Idle => format!("{} has nothing to do", name),
+        StallReason::MissingInput(item) => format!("{} is waiting for {}", name, item),
+        StallReason::OutputFull => format!("{} has a full output", name),
+        StallReason::Overheated => format!("{} has overheated", name),
+        StallReason::NoPower => format!("{} has no power", name),
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn check_alerts(
+    mut alerts: ResMut<Alerts>,
+    block_query: Query<(Entity, &Block, &Aabb, &GlobalTransform)>,
+    name_query: Query<&Name>,
+    process_query: Query<&Process>,
+    conveyor_query: Query<Entity, With<Conveyor>>,
+    grabber_query: Query<Entity, With<Grabber>>,
+    input_query: Query<(), With<components::Input>>,
+    output_query: Query<(), With<Output>>,
+    machine_query: Query<&Output, (Without<Conveyor>, Without<Storage>)>,
+    taker_query: Query<(&Block, &Aabb, &GlobalTransform), Or<(With<Grabber>, With<Pipe>)>>,
+    block_grid: Res<BlockGrid>,
+    time: Res<Time>,
+) {
+    alerts.timer.tick(time.delta());
+    if !alerts.timer.just_finished() {
+        return;
+    }
+    let alerts = &mut *alerts;
+
+    let name = |entity: Entity| block_name(entity, &name_query);
+
+    let mut raised = vec![];
+    for (entity, (reason, seconds)) in alerts.stalled_for.iter() {
+        if *seconds < STALL_SECONDS {
+            continue;
+        }
+        let no_reaction = process_query
+            .get(*entity)
+            .is_ok_and(|process| process.reaction.is_none());
+        if no_reaction {
+            raised.push(Alert {
+                entity: *entity,
+                kind: AlertKind::NoReaction,
+                message: format!("{} has no reaction set", name(*entity)),
+            });
+        } else if *reason != StallReason::Idle {
+            raised.push(Alert {
+                entity: *entity,
+                kind: AlertKind::Stalled(reason.clone()),
+                message: stall_message(&name(*entity), reason),
+            });
+        }
+    }
+
+    // grabbers and pipes pull from the block behind them
+    let taken_from = taker_query
+        .iter()
+        .filter_map(|(taker, aabb, trans)| {
+            block_grid.next_in_direction((aabb, trans), taker.direction.reverse())
+        })
+        .collect::<HashSet<_>>();
+
+    for (entity, block, aabb, trans) in block_query.iter() {
+        let ahead = block.direction.clone();
+        let behind = block.direction.reverse();
+        let (connected, message) = if conveyor_query.contains(entity) {
+            let connected = block_grid
+                .next_in_direction((aabb, trans), ahead)
+                .is_some_and(|other| other != entity);
+            (connected, "isn't connected to anything")
+        } else if grabber_query.contains(entity) {
+            let connected = block_grid
+                .next_in_direction((aabb, trans), ahead)
+                .is_some_and(|other| input_query.contains(other))
+                && block_grid
+                    .next_in_direction((aabb, trans), behind)
+                    .is_some_and(|other| output_query.contains(other));
+            (connected, "isn't connected to anything")
+        } else if let Ok(output) = machine_query.get(entity) {
+            // generators and the like never fill their output, so only complain once it backs up
+            if output.inventory.is_empty() {
+                continue;
+            }
+            (
+                taken_from.contains(&entity),
+                "has nothing taking its output",
+            )
+        } else {
+            continue;
+        };
+        if !connected {
+            raised.push(Alert {
+                entity,
+                kind: AlertKind::Unconnected,
+                message: format!("{} {}", name(entity), message),
+            });
+        }
+    }
+
+    // forget dismissals once their problem is fixed, so it can be raised again
+    alerts.dismissed.retain(|(entity, kind)| {
+        raised
+            .iter()
+            .any(|alert| alert.entity == *entity && alert.kind == *kind)
+    });
+    raised.retain(|alert| {
+        !alerts
+            .dismissed
+            .contains(&(alert.entity, alert.kind.clone()))
+    });
+    for alert in raised.iter() {
+        let new = !alerts
+            .alerts
+            .iter()
+            .any(|old| old.entity == alert.entity && old.kind == alert.kind);
+        if new {
+            warn!("{}", alert.message);
+        }
+    }
+    alerts.alerts = raised;
+}
+
+#[allow(clippy::type_complexity)]
+fn alerts_ui(
+    mut egui_ctx: EguiContexts,
+    mut alerts: ResMut<Alerts>,
+    mut commands: Commands,
+    mut camera_query: Query<(&mut PlayerPluginCamera, &mut Transform), With<Player>>,
+    block_query: Query<&GlobalTransform, With<Block>>,
+    selected_query: Query<Entity, With<BlockClicked>>,
+) {
+    if alerts.alerts.is_empty() {
+        return;
+    }
+
+    let mut focus = None;
+    let mut dismiss = None;
+    egui::Area::new("alerts")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10., -10.))
+        .show(egui_ctx.ctx_mut(), |ui| {
+            if alerts.alerts.len() > MAX_TOASTS {
+                ui.label(format!("and {} more", alerts.alerts.len() - MAX_TOASTS));
+            }
+            for alert in alerts.alerts.iter().take(MAX_TOASTS) {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.link(&alert.message).clicked() {
+                            focus = Some(alert.entity);
+                        }
+                        if ui.small_button("x").clicked() {
+                            dismiss = Some(alert.clone());
+                        }
+                    });
+                });
+            }
+        });
+
+    if let Some(alert) = dismiss {
+        alerts
+            .alerts
+            .retain(|other| other.entity != alert.entity || other.kind != alert.kind);
+        alerts.dismissed.insert((alert.entity, alert.kind));
+    }
+
+    let Some(entity) = focus else {
+        return;
+    };
+    let Ok(target) = block_query.get(entity) else {
+        return;
+    };
+    // select the block too, so its details are in the side panel
+    for selected in selected_query.iter() {
+        commands.entity(selected).remove::<BlockClicked>();
+    }
+    commands.entity(entity).insert(BlockClicked {});
+    for (mut camera, mut transform) in camera_query.iter_mut() {
+        camera.focus = target.translation();
+        transform.translation = camera.focus
+            + transform
+                .rotation
+                .mul_vec3(Vec3::new(0., 0., camera.radius));
+    }
+}
@@ -0,0 +1,302 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::primitives::Aabb,
+    utils::{HashMap, HashSet},
+};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+use bevy_prototype_debug_lines::DebugShapes;
+
+use crate::{
+    blocks::block_name,
+    components::{Block, BlockStatus, Utilisation},
+    events::{BlockRemoved, ItemTransferred, StallReason},
+    ui::not_typing,
+};
+
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Links>()
+            .init_resource::<Analysis>()
+            .add_system(utilisation_system)
+            .add_system(track_links)
+            .add_system(analyse_bottlenecks)
+            .add_system(analysis_hotkey.run_if(not_typing))
+            .add_system(highlight_bottlenecks)
+            .add_system(analysis_ui);
+    }
+}
+
+/// Fraction of the time stalled before a block is reported.
+const STALL_THRESHOLD: f32 = 0.25;
+
+/// Fraction of the time working before a block in a struggling chain is taken to be limiting it.
+const BUSY_THRESHOLD: f32 = 0.75;
+
+/// Seconds of utilisation needed before a block is judged at all.
+const MIN_OBSERVED: f32 = 5.;
+
+const MAX_HIGHLIGHTS: usize = 5;
+
+/// Which blocks have moved items between each other, in either direction.
+#[derive(Resource, Default)]
+pub struct Links {
+    pub neighbours: HashMap<Entity, HashSet<Entity>>,
+}
+
+impl Links {
+    /// Groups `blocks` with everything they are linked to, through blocks that aren't in `blocks`
+    /// too, such as pipes and storage.
+    pub fn chains(&self, blocks: &[Entity]) -> Vec<Vec<Entity>> {
+        let mut chain_of: HashMap<Entity, usize> = HashMap::new();
+        let mut chains = vec![];
+        for block in blocks {
+            if chain_of.contains_key(block) {
+                continue;
+            }
+            let mut chain = vec![];
+            let mut stack = vec![*block];
+            chain_of.insert(*block, chains.len());
+            while let Some(next) = stack.pop() {
+                if blocks.contains(&next) {
+                    chain.push(next);
+                }
+                for neighbour in self.neighbours.get(&next).into_iter().flatten() {
+                    if !chain_of.contains_key(neighbour) {
+                        chain_of.insert(*neighbour, chains.len());
+                        stack.push(*neighbour);
+                    }
+                }
+            }
+            chains.push(chain);
+        }
+        chains
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub entity: Entity,
+    pub chain: usize,
+    /// Fraction of the time it has been stalled, or busy when it is limiting.
+    pub severity: f32,
+    /// Busy while the rest of its chain waits on it, rather than stalled itself.
+    pub limiting: bool,
+    pub explanation: String,
+}
+
+#[derive(Resource)]
+pub struct Analysis {
+    pub open: bool,
+    /// Worst first.
+    pub findings: Vec<Finding>,
+    timer: Timer,
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Self {
+            open: false,
+            findings: vec![],
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+        }
+    }
+}
+
+fn utilisation_system(mut query: Query<(&BlockStatus, &mut Utilisation)>, time: Res<Time>) {
+    for (status, mut utilisation) in query.iter_mut() {
+        utilisation.record(status.stalled.as_ref(), time.delta_seconds());
+    }
+}
+
+fn track_links(
+    mut links: ResMut<Links>,
+    mut transferred: EventReader<ItemTransferred>,
+    mut removed: EventReader<BlockRemoved>,
+) {
+    for event in transferred.iter() {
+        links
+            .neighbours
+            .entry(event.from)
+            .or_default()
+            .insert(event.to);
+        links
+            .neighbours
+            .entry(event.to)
+            .or_default()
+            .insert(event.from);
+    }
+    for event in removed.iter() {
+        for neighbour in links.neighbours.remove(&event.entity).into_iter().flatten() {
+            if let Some(neighbours) = links.neighbours.get_mut(&neighbour) {
+                neighbours.remove(&event.entity);
+            }
+        }
+    }
+}
+
+fn describe(name: &str, reason: &StallReason, fraction: f32) -> String {
+    let percent = (fraction * 100.).round();
+    match reason {
+        StallReason::MissingInput(item) => {
+            format!("{} starved of {} {}% of the time", name, item, percent)
+        }
+        StallReason::OutputFull => {
+            format!("{} blocked by a full output {}% of the time", name, percent)
+        }
+        StallReason::Overheated => format!("{} overheated {}% of the time", name, percent),
+        StallReason::NoPower => format!("{} without power {}% of the time", name, percent),
+        StallReason::Idle => format!("{} idle", name),
+    }
+}
+
+fn analyse_bottlenecks(
+    mut analysis: ResMut<Analysis>,
+    links: Res<Links>,
+    query: Query<(Entity, &Utilisation, &Block)>,
+    name_query: Query<&Name>,
+    time: Res<Time>,
+) {
+    analysis.timer.tick(time.delta());
+    if !analysis.timer.just_finished() {
+        return;
+    }
+
+    let observed = query
+        .iter()
+        .filter(|(_, utilisation, _)| utilisation.total() >= MIN_OBSERVED)
+        .map(|(entity, _, _)| entity)
+        .collect::<Vec<_>>();
+    let name = |entity: Entity| block_name(entity, &name_query);
+
+    let mut findings = vec![];
+    for (chain, blocks) in links.chains(&observed).into_iter().enumerate() {
+        let mut struggling = false;
+        for block in blocks.iter() {
+            let Ok((_, utilisation, _)) = query.get(*block) else {
+                continue;
+            };
+            let Some((reason, fraction)) = utilisation.worst_stall() else {
+                continue;
+            };
+            if fraction < STALL_THRESHOLD {
+                continue;
+            }
+            struggling = true;
+            findings.push(Finding {
+                entity: *block,
+                chain,
+                severity: fraction,
+                limiting: false,
+                explanation: describe(&name(*block), reason, fraction),
+            });
+        }
+
+        // when things are waiting, the machine that never stops is the one holding them up.
+        // Belts and grabbers are busy whenever anything moves, so they can't be blamed.
+        if !struggling {
+            continue;
+        }
+        let busiest = blocks
+            .iter()
+            .filter_map(|block| query.get(*block).ok())
+            .filter(|(_, _, block)| !block.block_type.is_transport())
+            .map(|(entity, utilisation, _)| (entity, utilisation.working_fraction()))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((block, busy)) = busiest.filter(|(_, busy)| *busy >= BUSY_THRESHOLD) {
+            findings.push(Finding {
+                entity: block,
+                chain,
+                severity: busy,
+                limiting: true,
+                explanation: format!(
+                    "{} busy {}% of the time, limiting its chain",
+                    name(block),
+                    (busy * 100.).round()
+                ),
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        b.limiting
+            .cmp(&a.limiting)
+            .then(b.severity.total_cmp(&a.severity))
+    });
+    analysis.findings = findings;
+}
+
+fn analysis_hotkey(keys: Res<Input<KeyCode>>, mut analysis: ResMut<Analysis>) {
+    if keys.just_pressed(KeyCode::B) {
+        analysis.open = !analysis.open;
+    }
+}
+
+fn highlight_bottlenecks(
+    analysis: Res<Analysis>,
+    query: Query<(&Aabb, &GlobalTransform)>,
+    mut shapes: ResMut<DebugShapes>,
+) {
+    if !analysis.open {
+        return;
+    }
+    for finding in analysis.findings.iter().take(MAX_HIGHLIGHTS) {
+        let Ok((aabb, trans)) = query.get(finding.entity) else {
+            continue;
+        };
+        let color = if finding.limiting {
+            Color::RED
+        } else {
+            Color::ORANGE
+        };
+        shapes
+            .cuboid()
+            .min_max(
+                trans.transform_point(aabb.min().into()) - Vec3::splat(0.05),
+                trans.transform_point(aabb.max().into()) + Vec3::splat(0.05),
+            )
+            .color(color)
+            .duration(0.);
+    }
+}
+
+fn analysis_ui(mut egui_ctx: EguiContexts, mut analysis: ResMut<Analysis>) {
+    if !analysis.open {
+        return;
+    }
+    let analysis = &mut *analysis;
+
+    egui::Window::new("Bottlenecks")
+        .open(&mut analysis.open)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            if analysis.findings.is_empty() {
+                ui.label("Nothing is holding the factory up");
+                return;
+            }
+            // chains in the order of their worst finding
+            let mut chains: Vec<usize> = vec![];
+            for finding in analysis.findings.iter() {
+                if !chains.contains(&finding.chain) {
+                    chains.push(finding.chain);
+                }
+            }
+            for chain in chains {
+                ui.strong(format!("Chain {}", chain + 1));
+                for finding in analysis
+                    .findings
+                    .iter()
+                    .filter(|finding| finding.chain == chain)
+                {
+                    let color = if finding.limiting {
+                        egui::Color32::RED
+                    } else {
+                        egui::Color32::from_rgb(255, 165, 0)
+                    };
+                    ui.colored_label(color, &finding.explanation);
+                }
+            }
+        });
+}
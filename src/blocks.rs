@@ -6,34 +6,53 @@ use bevy::{
 use bevy_mod_picking::PickableBundle;
 use bevy_prototype_debug_lines::DebugShapes;
 use enum_iterator::Sequence;
-use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, time::Duration};
 
 use crate::{
     components::{
-        self, Block, BlockClicked, Conveyor, Furnace, Grabber, Output, Process, Splitter, Storage,
+        self, Accumulator, Block, BlockClicked, BlockStatus, Centrifuge, Converter, Conveyor,
+        Furnace, Grabber, Output, PhaseChanger, Process, Source, Splitter, Storage, Utilisation,
     },
-    grid::GridCellHoveredEvent,
+    events::{
+        BlockStalled, ItemConsumed, ItemProduced, ItemTransferred, ReactionCompleted, StallReason,
+    },
+    fluids::{Compressor, FluidStorage, Pipe, Tank, Valve},
+    grid::{BlockGrid, GridCellHoveredEvent},
+    heat::Temperature,
+    materials::{Element, Energy, ItemStackType, StackLimits, State},
     player::{self, Modes, Player, SpawnerOptions},
+    power::{power_network_system, Generator, PowerConnection, PowerConsumer, PowerPole},
+    radiation::Radiation,
+    research::Lab,
+    time_controls::simulation_running,
 };
 
 pub struct BlockPlugin;
 
 impl Plugin for BlockPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(furnace_system);
-        app.add_system(internal_conveyor_system);
-        app.add_system(external_conveyor_system);
-        app.add_system(grabber_system);
+        app.add_system(furnace_system.run_if(simulation_running));
+        app.add_system(internal_conveyor_system.run_if(simulation_running));
+        app.add_system(external_conveyor_system.run_if(simulation_running));
+        app.add_system(grabber_system.run_if(simulation_running));
+        app.add_system(splitter_system.run_if(simulation_running));
+        app.add_system(extractor_system.run_if(simulation_running));
+        app.add_system(
+            converter_system
+                .before(power_network_system)
+                .run_if(simulation_running),
+        );
+        app.add_system(accumulator_system.run_if(simulation_running));
+        app.add_system(centrifuge_system.run_if(simulation_running));
+        app.add_system(phase_change_system.run_if(simulation_running));
         app.add_system(display_build_ghost_system);
         app.add_system(highlight_selected_block);
     }
 }
 
-pub fn is_next_block_in_direction(
-    a: (&Aabb, &GlobalTransform),
-    b: (&Aabb, &GlobalTransform),
-    direction: player::Direction,
-) -> bool {
+/// The middle of the cell just past the `direction` face of `a`.
+pub fn point_in_direction(a: (&Aabb, &GlobalTransform), direction: player::Direction) -> Vec3 {
     let mut target_vec = a.1.transform_point(a.0.center.into());
     match direction {
         player::Direction::North => {
@@ -55,21 +74,34 @@ pub fn is_next_block_in_direction(
             target_vec.y = (target_vec.y - a.0.half_extents.y).floor() - 0.5;
         }
     }
-
-    let block_aabb = (
-        b.1.transform_point(b.0.min().into()).floor(),
-        b.1.transform_point(b.0.max().into()).ceil(),
-    );
-
-    target_vec.x >= block_aabb.0.x
-        && target_vec.x <= block_aabb.1.x
-        && target_vec.y >= block_aabb.0.y
-        && target_vec.y <= block_aabb.1.y
-        && target_vec.z >= block_aabb.0.z
-        && target_vec.z <= block_aabb.1.z
+    target_vec
 }
 
-#[derive(Debug, Clone, Reflect, Copy, Default, PartialEq, Eq, Hash, Sequence)]
+/// Heat given off by a furnace per second of reaction it completes.
+const REACTION_HEAT: f32 = 400.;
+
+/// Share of the fuel a burner consumes that ends up heating the burner itself, per unit.
+const BURNER_WASTE_HEAT: f32 = 50.;
+
+/// Power drawn by the electric phase changers. A cycle needs the element's
+/// `phase_change_energy` worth of it, so harder changes take longer.
+const PHASE_CHANGE_DEMAND: f32 = 20.;
+
+/// What an extractor can be set to dig up, the first is what a new one starts on.
+pub const EXTRACTABLE: [Element; 8] = [
+    Element::Iron,
+    Element::Copper,
+    Element::Silicon,
+    Element::Aluminium,
+    Element::Carbon,
+    Element::Lithium,
+    Element::Nickel,
+    Element::Tungsten,
+];
+
+#[derive(
+    Debug, Clone, Reflect, Copy, Default, PartialEq, Eq, Hash, Sequence, Serialize, Deserialize,
+)]
 pub enum BlockType {
     #[default]
     Debug,
@@ -78,6 +110,59 @@ pub enum BlockType {
     Splitter,
     Storage,
     Grabber,
+    Generator,
+    PowerPole,
+    Turbine,
+    Heater,
+    Photovoltaic,
+    Motor,
+    Accumulator,
+    Centrifuge,
+    Pipe,
+    Tank,
+    Melter,
+    Boiler,
+    Condenser,
+    Freezer,
+    PlasmaTorch,
+    Compressor,
+    Valve,
+    Extractor,
+    Lab,
+}
+
+impl BlockType {
+    /// Temperature in kelvin past which the block stops working.
+    pub fn max_temperature(&self) -> f32 {
+        match self {
+            BlockType::Furnace | BlockType::Melter | BlockType::Boiler => 2500.,
+            BlockType::PlasmaTorch => 5000.,
+            BlockType::Conveyor | BlockType::Grabber | BlockType::PowerPole => 600.,
+            _ => 1000.,
+        }
+    }
+
+    /// Whether a system keeps its [`BlockStatus`] up to date. Pipes, tanks, storage and poles
+    /// have nothing that can stall.
+    pub fn reports_status(&self) -> bool {
+        !matches!(
+            self,
+            BlockType::Debug
+                | BlockType::Storage
+                | BlockType::PowerPole
+                | BlockType::Pipe
+                | BlockType::Tank
+                | BlockType::Valve
+        )
+    }
+
+    /// Blocks that only move items along, so they are busy whenever anything flows through them.
+    pub fn is_transport(&self) -> bool {
+        matches!(
+            self,
+            BlockType::Conveyor | BlockType::Grabber | BlockType::Splitter
+        )
+    }
 }
 
 impl Display for BlockType {
@@ -86,6 +171,13 @@ impl Display for BlockType {
     }
 }
 
+/// How a block is named in reports, with its entity so two of the same kind can be told apart.
+pub fn block_name(entity: Entity, names: &Query<&Name>) -> String {
+    names.get(entity).map_or(format!("{:?}", entity), |name| {
+        format!("{} {:?}", name, entity)
+    })
+}
+
 pub trait Spawn {
     fn spawn(
         &self,
@@ -95,7 +187,7 @@ pub trait Spawn {
         asset_server: &Res<AssetServer>,
         spawner_options: &SpawnerOptions,
         click_position: Vec3,
-    );
+    ) -> Entity;
 }
 
 impl Spawn for BlockType {
@@ -107,12 +199,12 @@ impl Spawn for BlockType {
         asset_server: &Res<AssetServer>,
         spawner_options: &SpawnerOptions,
         click_position: Vec3,
-    ) {
+    ) -> Entity {
         let default_block = Block {
             block_type: BlockType::Debug,
             direction: spawner_options.block_rotation.clone(),
         };
-        match self {
+        let mut entity = match self {
             BlockType::Debug => commands.spawn((
                 SceneBundle {
                     scene: asset_server.load(r"models\test.gltf#Scene0"),
@@ -144,7 +236,7 @@ impl Spawn for BlockType {
                     ..default()
                 },
                 Name::new("Furnace"),
-                Furnace::default(),
+                Furnace,
                 Block {
                     block_type: BlockType::Furnace,
                     ..default_block
@@ -152,6 +244,8 @@ impl Spawn for BlockType {
                 components::Input::default(),
                 Output::default(),
                 Process::default(),
+                PowerConsumer::new(20.),
+                PowerConnection::default(),
                 PickableBundle::default(),
             )),
             BlockType::Conveyor => commands.spawn((
@@ -205,13 +299,14 @@ impl Spawn for BlockType {
                     ..default()
                 },
                 Name::new("Storage"),
-                Storage::default(),
+                Storage,
                 Block {
                     block_type: BlockType::Storage,
                     ..default_block
                 },
                 components::Input::default(),
                 Output::default(),
+                Radiation::default(),
                 PickableBundle::default(),
             )),
             BlockType::Grabber => commands.spawn((
@@ -229,115 +324,1209 @@ impl Spawn for BlockType {
                     block_type: BlockType::Grabber,
                     ..default_block
                 },
+                PowerConsumer::new(5.),
+                PowerConnection::default(),
+                PickableBundle::default(),
+                Aabb {
+                    half_extents: Vec3A::new(0.5, 0.5, 0.5),
+                    ..Default::default()
+                },
+            )),
+            BlockType::Generator => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(2.0, 2.0, 2.0).into()),
+                    material: materials.add(Color::ORANGE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0., 1., 0.),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Generator"),
+                Converter::generator(),
+                Generator::default(),
+                Block {
+                    block_type: BlockType::Generator,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::PowerPole => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(0.2, 3.0, 0.2).into()),
+                    material: materials.add(Color::GRAY.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 1.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Power Pole"),
+                PowerPole::default(),
+                Block {
+                    block_type: BlockType::PowerPole,
+                    ..default_block
+                },
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Turbine => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::SILVER.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Turbine"),
+                Converter::turbine(),
+                Block {
+                    block_type: BlockType::Turbine,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Heater => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::ORANGE_RED.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Heater"),
+                Converter::heater(),
+                PowerConsumer::new(Converter::heater().input_per_second()),
+                Block {
+                    block_type: BlockType::Heater,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Photovoltaic => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 0.1, 1.0).into()),
+                    material: materials.add(Color::MIDNIGHT_BLUE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.05, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Photovoltaic"),
+                Converter::photovoltaic(),
+                Generator::default(),
+                Block {
+                    block_type: BlockType::Photovoltaic,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Motor => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::TEAL.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Motor"),
+                Converter::motor(),
+                PowerConsumer::new(Converter::motor().input_per_second()),
+                Block {
+                    block_type: BlockType::Motor,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Accumulator => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::LIME_GREEN.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Accumulator"),
+                Accumulator::default(),
+                Block {
+                    block_type: BlockType::Accumulator,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Centrifuge => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(
+                        shape::Cylinder {
+                            radius: 0.5,
+                            height: 2.0,
+                            ..default()
+                        }
+                        .into(),
+                    ),
+                    material: materials.add(Color::AQUAMARINE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 1.0, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Centrifuge"),
+                Centrifuge::default(),
+                Block {
+                    block_type: BlockType::Centrifuge,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PowerConsumer::new(30.),
+                PowerConnection::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Pipe => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 0.4, 0.4).into()),
+                    material: materials.add(Color::DARK_GRAY.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Pipe"),
+                Pipe,
+                FluidStorage::new(10),
+                Block {
+                    block_type: BlockType::Pipe,
+                    ..default_block
+                },
+                PickableBundle::default(),
+                Aabb {
+                    half_extents: Vec3A::new(0.5, 0.5, 0.5),
+                    ..Default::default()
+                },
+            )),
+            BlockType::Compressor => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 0.8, 0.8).into()),
+                    material: materials.add(Color::BEIGE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Compressor"),
+                Compressor::default(),
+                Block {
+                    block_type: BlockType::Compressor,
+                    ..default_block
+                },
+                PowerConsumer::new(15.),
+                PowerConnection::default(),
                 PickableBundle::default(),
                 Aabb {
                     half_extents: Vec3A::new(0.5, 0.5, 0.5),
                     ..Default::default()
                 },
             )),
+            BlockType::Valve => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(0.6, 0.6, 0.6).into()),
+                    material: materials.add(Color::GOLD.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Valve"),
+                Valve::default(),
+                FluidStorage::new(5),
+                Block {
+                    block_type: BlockType::Valve,
+                    ..default_block
+                },
+                PickableBundle::default(),
+                Aabb {
+                    half_extents: Vec3A::new(0.5, 0.5, 0.5),
+                    ..Default::default()
+                },
+            )),
+            BlockType::Extractor => {
+                let cell = click_position.floor();
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                        material: materials.add(Color::DARK_GRAY.into()),
+                        transform: Transform::from_translation(cell + vec3(0.5, 0.5, 0.5))
+                            .with_rotation(spawner_options.block_rotation.to_quat()),
+                        ..default()
+                    },
+                    Name::new("Extractor"),
+                    Source::new(
+                        EXTRACTABLE[0].to_item_stack(State::Solid, 1),
+                        Duration::from_secs(2),
+                    ),
+                    Block {
+                        block_type: BlockType::Extractor,
+                        ..default_block
+                    },
+                    Output::default(),
+                    PickableBundle::default(),
+                ))
+            }
+            BlockType::Lab => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::CYAN.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Lab"),
+                Lab::default(),
+                Block {
+                    block_type: BlockType::Lab,
+                    ..default_block
+                },
+                components::Input::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Tank => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(
+                        shape::Cylinder {
+                            radius: 1.0,
+                            height: 2.0,
+                            ..default()
+                        }
+                        .into(),
+                    ),
+                    material: materials.add(Color::WHITE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0., 1., 0.),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Tank"),
+                Tank::default(),
+                FluidStorage::new(500),
+                Block {
+                    block_type: BlockType::Tank,
+                    ..default_block
+                },
+                PickableBundle::default(),
+            )),
+            BlockType::Melter => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::MAROON.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Melter"),
+                PhaseChanger::melter(),
+                Block {
+                    block_type: BlockType::Melter,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Boiler => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::CRIMSON.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Boiler"),
+                PhaseChanger::boiler(),
+                Block {
+                    block_type: BlockType::Boiler,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Condenser => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::CYAN.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Condenser"),
+                PhaseChanger::condenser(),
+                PowerConsumer::new(PHASE_CHANGE_DEMAND),
+                PowerConnection::default(),
+                Block {
+                    block_type: BlockType::Condenser,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::Freezer => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::ALICE_BLUE.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Freezer"),
+                PhaseChanger::freezer(),
+                PowerConsumer::new(PHASE_CHANGE_DEMAND),
+                PowerConnection::default(),
+                Block {
+                    block_type: BlockType::Freezer,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PickableBundle::default(),
+            )),
+            BlockType::PlasmaTorch => commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+                    material: materials.add(Color::VIOLET.into()),
+                    transform: Transform::from_translation(
+                        click_position.floor() + vec3(0.5, 0.5, 0.5),
+                    )
+                    .with_rotation(spawner_options.block_rotation.to_quat()),
+                    ..default()
+                },
+                Name::new("Plasma Torch"),
+                PhaseChanger::plasma_torch(),
+                PowerConsumer::new(PHASE_CHANGE_DEMAND),
+                PowerConnection::default(),
+                Block {
+                    block_type: BlockType::PlasmaTorch,
+                    ..default_block
+                },
+                components::Input::default(),
+                Output::default(),
+                PickableBundle::default(),
+            )),
         };
+        entity.insert(Temperature::new(self.max_temperature()));
+        if self.reports_status() {
+            entity.insert((BlockStatus::default(), Utilisation::default()));
+        }
+        entity.id()
     }
 }
 
+/// Records why a block isn't working, only announcing it when the reason changes.
+pub fn set_status(
+    entity: Entity,
+    status: &mut BlockStatus,
+    stall: Option<StallReason>,
+    stalled: &mut EventWriter<BlockStalled>,
+) {
+    if status.stalled == stall {
+        return;
+    }
+    if let Some(reason) = stall.clone() {
+        stalled.send(BlockStalled { entity, reason });
+    }
+    status.stalled = stall;
+}
+
+#[allow(clippy::type_complexity)]
 fn furnace_system(
-    mut query: Query<(&mut components::Input, &mut Output, &mut Process), With<Furnace>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut components::Input,
+            &mut Output,
+            &mut Process,
+            &mut BlockStatus,
+            Option<&PowerConsumer>,
+            Option<&mut Temperature>,
+        ),
+        With<Furnace>,
+    >,
     time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut produced: EventWriter<ItemProduced>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut completed: EventWriter<ReactionCompleted>,
+    mut stalled: EventWriter<BlockStalled>,
 ) {
-    for (mut input, mut output, mut process) in query.iter_mut() {
-        if process.reaction.is_none() {
+    for (ent, mut input, mut output, mut process, mut status, power, mut temperature) in
+        query.iter_mut()
+    {
+        let Some(reaction) = process.reaction.as_ref() else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
             continue;
         };
 
-        if !process
-            .reaction
-            .as_ref()
-            .unwrap()
-            .valid_input(&input.inventory)
-        {
+        let speed = power.map_or(1., |power| power.satisfaction);
+        let missing = reaction
+            .input
+            .iter()
+            .find(|item| !input.inventory.contains_matching(item));
+        let stall =
+            if temperature
+                .as_ref()
+                .is_some_and(|temperature| temperature.is_overheated())
+            {
+                Some(StallReason::Overheated)
+            } else if let Some(missing) = missing {
+                Some(StallReason::MissingInput(missing.item_type.clone()))
+            } else if reaction.output.iter().any(|item| {
+                output.inventory.quantity(&item.item_type) >= limits.limit(&item.item_type)
+            }) {
+                Some(StallReason::OutputFull)
+            } else if speed <= 0. {
+                Some(StallReason::NoPower)
+            } else {
+                None
+            };
+        let stopped = stall.is_some();
+        set_status(ent, &mut status, stall, &mut stalled);
+        if stopped {
             continue;
         }
 
-        process.timer.tick(time.delta());
+        process.timer.tick(time.delta().mul_f32(speed));
         if process.timer.just_finished() {
-            process
-                .reaction
-                .as_ref()
-                .unwrap()
-                .run(&mut input.inventory, &mut output.inventory);
+            let reaction = process.reaction.as_ref().unwrap();
+            reaction.run(&mut input.inventory, &mut output.inventory, &limits);
+            for item in reaction.input.iter() {
+                consumed.send(ItemConsumed {
+                    entity: ent,
+                    item: item.clone(),
+                });
+            }
+            for item in reaction.output.iter() {
+                produced.send(ItemProduced {
+                    entity: ent,
+                    item: item.clone(),
+                });
+            }
+            completed.send(ReactionCompleted {
+                entity: ent,
+                reaction: reaction.clone(),
+            });
+            if let Some(temperature) = temperature.as_mut() {
+                temperature.add_heat(REACTION_HEAT * process.timer.duration().as_secs_f32());
+            }
             process.timer.reset();
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn internal_conveyor_system(
-    mut query: Query<(&mut components::Input, &mut Output, &mut Conveyor)>,
+    mut query: Query<(
+        Entity,
+        &mut components::Input,
+        &mut Output,
+        &mut Conveyor,
+        &mut BlockStatus,
+        Option<&Temperature>,
+    )>,
     time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut transferred: EventWriter<ItemTransferred>,
+    mut stalled: EventWriter<BlockStalled>,
 ) {
-    for (mut input, mut output, mut conveyor) in query.iter_mut() {
+    for (ent, mut input, mut output, mut conveyor, mut status, temperature) in query.iter_mut() {
+        if temperature.is_some_and(|temperature| temperature.is_overheated()) {
+            set_status(
+                ent,
+                &mut status,
+                Some(StallReason::Overheated),
+                &mut stalled,
+            );
+            continue;
+        }
         conveyor.timer.tick(time.delta());
         if conveyor.timer.finished() {
             if let Some(item) = input.inventory.pop() {
-                output.inventory.push(item);
+                output.inventory.push(item.clone(), &limits);
+                transferred.send(ItemTransferred {
+                    from: ent,
+                    to: ent,
+                    item,
+                });
+                set_status(ent, &mut status, None, &mut stalled);
+            } else {
+                set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
             }
             conveyor.timer.reset();
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn external_conveyor_system(
     mut input_query: Query<
-        (&Aabb, &GlobalTransform, &Block, &mut components::Input),
+        (
+            Entity,
+            &Aabb,
+            &GlobalTransform,
+            &Block,
+            &mut components::Input,
+            Option<&Temperature>,
+        ),
         With<Conveyor>,
     >,
-    mut output_query: Query<(&Aabb, &GlobalTransform, &Block, &mut Output), With<Conveyor>>,
+    mut output_query: Query<&mut Output, With<Conveyor>>,
+    block_grid: Res<BlockGrid>,
+    mut transferred: EventWriter<ItemTransferred>,
+    limits: Res<StackLimits>,
 ) {
-    for (aabb, trans, block, mut input) in input_query.iter_mut() {
-        let output = output_query.iter_mut().find(|(ab, tr, _, _)| {
-            is_next_block_in_direction((aabb, trans), (ab, tr), block.direction.reverse())
-        });
+    for (to, aabb, trans, block, mut input, temperature) in input_query.iter_mut() {
+        if temperature.is_some_and(|temperature| temperature.is_overheated()) {
+            continue;
+        }
+        let output = block_grid
+            .next_in_direction((aabb, trans), block.direction.reverse())
+            .and_then(|from| Some((from, output_query.get_mut(from).ok()?)));
 
-        let Some((_,_,_, mut output)) = output else {
+        let Some((from, mut output)) = output else {
             continue;
         };
 
-        if let Some(accepts) = input.accepts.clone() {
-            if !output.inventory.is_empty() && output.inventory.contains(&accepts) {
-                output.inventory.transfer(&accepts, &mut input.inventory);
+        // fluids only travel by pipe unless they are in a container
+        let moved = if let Some(accepts) = input.accepts.clone() {
+            if accepts.item_type.is_conveyable()
+                && !output.inventory.is_empty()
+                && output.inventory.contains(&accepts)
+            {
+                output
+                    .inventory
+                    .transfer(&accepts, &mut input.inventory, &limits)
+            } else {
+                None
             }
         } else {
-            output.inventory.transfer_first(&mut input.inventory);
+            output
+                .inventory
+                .transfer_first_where(&mut input.inventory, &limits, |item| {
+                    item.item_type.is_conveyable()
+                })
+        };
+        if let Some(item) = moved {
+            transferred.send(ItemTransferred { from, to, item });
         }
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn grabber_system(
-    grabber_query: Query<(&Block, &Aabb, &GlobalTransform), With<Grabber>>,
-    mut input_query: Query<(&Aabb, &GlobalTransform, &mut components::Input)>,
-    mut output_query: Query<(&Aabb, &GlobalTransform, &mut Output)>,
+    mut grabber_query: Query<(
+        Entity,
+        &Block,
+        &Aabb,
+        &GlobalTransform,
+        &mut Grabber,
+        &mut BlockStatus,
+        Option<&PowerConsumer>,
+        Option<&Temperature>,
+    )>,
+    mut input_query: Query<&mut components::Input>,
+    mut output_query: Query<&mut Output>,
+    block_grid: Res<BlockGrid>,
+    mut transferred: EventWriter<ItemTransferred>,
+    mut stalled: EventWriter<BlockStalled>,
+    limits: Res<StackLimits>,
+    time: Res<Time>,
     // mut debug_lines: ResMut<DebugShapes>,
 ) {
-    for (block, aabb, trans) in grabber_query.iter() {
-        let input = input_query.iter_mut().find(|(ab, tr, _)| {
-            is_next_block_in_direction((aabb, trans), (ab, tr), block.direction.clone())
-        });
-        let output = output_query.iter_mut().find(|(ab, tr, _)| {
-            is_next_block_in_direction((aabb, trans), (ab, tr), block.direction.reverse())
+    for (ent, block, aabb, trans, mut grabber, mut status, power, temperature) in
+        grabber_query.iter_mut()
+    {
+        if temperature.is_some_and(|temperature| temperature.is_overheated()) {
+            set_status(
+                ent,
+                &mut status,
+                Some(StallReason::Overheated),
+                &mut stalled,
+            );
+            continue;
+        }
+        let speed = power.map_or(1., |power| power.satisfaction);
+        if speed <= 0. {
+            set_status(ent, &mut status, Some(StallReason::NoPower), &mut stalled);
+            continue;
+        }
+        // a grabber only ever moves one item at a time, so a long frame can't bank moves
+        grabber.progress =
+            (grabber.progress + Grabber::RATE * speed * time.delta_seconds()).min(1.);
+        if grabber.progress < 1. {
+            continue;
+        }
+        grabber.progress -= 1.;
+
+        let input = block_grid
+            .next_in_direction((aabb, trans), block.direction.clone())
+            .and_then(|to| Some((to, input_query.get_mut(to).ok()?)));
+        let output = block_grid
+            .next_in_direction((aabb, trans), block.direction.reverse())
+            .and_then(|from| Some((from, output_query.get_mut(from).ok()?)));
+
+        let (Some((to, mut input)), Some((from, mut output))) = (input, output) else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+            continue;
+        };
+
+        // fluids only travel by pipe unless they are in a container
+        let moved = if let Some(accepts) = input.accepts.clone() {
+            if accepts.item_type.is_conveyable()
+                && !output.inventory.is_empty()
+                && output.inventory.contains(&accepts)
+            {
+                output
+                    .inventory
+                    .transfer(&accepts, &mut input.inventory, &limits)
+            } else {
+                None
+            }
+        } else {
+            output
+                .inventory
+                .transfer_first_where(&mut input.inventory, &limits, |item| {
+                    item.item_type.is_conveyable()
+                })
+        };
+        if let Some(item) = moved {
+            transferred.send(ItemTransferred { from, to, item });
+            set_status(ent, &mut status, None, &mut stalled);
+        } else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+        }
+    }
+}
+
+/// Digs up one of the source every cycle until a full stack is waiting in the output.
+fn extractor_system(
+    mut query: Query<(Entity, &mut Source, &mut Output, &mut BlockStatus)>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut produced: EventWriter<ItemProduced>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, mut source, mut output, mut status) in query.iter_mut() {
+        let Some(stack) = source.source.clone() else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+            continue;
+        };
+        if output.inventory.quantity(&stack.item_type) >= limits.limit(&stack.item_type) {
+            set_status(
+                ent,
+                &mut status,
+                Some(StallReason::OutputFull),
+                &mut stalled,
+            );
+            continue;
+        }
+        set_status(ent, &mut status, None, &mut stalled);
+        source.timer.tick(time.delta());
+        if !source.timer.just_finished() {
+            continue;
+        }
+        output.inventory.push(stack.clone(), &limits);
+        produced.send(ItemProduced {
+            entity: ent,
+            item: stack,
         });
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn splitter_system(
+    mut splitter_query: Query<(
+        Entity,
+        &Block,
+        &Aabb,
+        &GlobalTransform,
+        &mut Splitter,
+        &mut BlockStatus,
+    )>,
+    mut input_query: Query<(&Block, &mut components::Input)>,
+    mut output_query: Query<&mut Output, With<Splitter>>,
+    block_grid: Res<BlockGrid>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut transferred: EventWriter<ItemTransferred>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, block, aabb, trans, mut splitter, mut status) in splitter_query.iter_mut() {
+        splitter.timer.tick(time.delta());
+        if !splitter.timer.just_finished() {
+            continue;
+        }
 
-        let Some((_, _, mut input)) = input else {
+        let Ok((_, input)) = input_query.get(ent) else {
             continue;
         };
+        let Some(index) = input
+            .inventory
+            .items
+            .iter()
+            .position(|item| item.item_type.is_conveyable())
+        else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+            continue;
+        };
+
+        let straight = match &splitter.filter {
+            Some(filter) => input.inventory.items[index].item_type.element() == Some(filter),
+            None => !splitter.alternate,
+        };
 
-        let Some((_, _, mut output)) = output else {
+        // only hands items on to other transport, and holds on to them until there is room
+        let target = if straight {
+            output_query.get(ent).ok().map(|output| {
+                (
+                    ent,
+                    output
+                        .inventory
+                        .has_room(&input.inventory.items[index], &limits),
+                )
+            })
+        } else {
+            block_grid
+                .next_in_direction((aabb, trans), block.direction.right())
+                .filter(|other| *other != ent)
+                .and_then(|other| {
+                    let (other_block, side_input) = input_query.get(other).ok()?;
+                    other_block.block_type.is_transport().then(|| {
+                        (
+                            other,
+                            side_input
+                                .inventory
+                                .has_room(&input.inventory.items[index], &limits),
+                        )
+                    })
+                })
+        };
+        let Some((to, true)) = target else {
+            set_status(
+                ent,
+                &mut status,
+                Some(StallReason::OutputFull),
+                &mut stalled,
+            );
             continue;
         };
 
-        if let Some(accepts) = input.accepts.clone() {
-            if !output.inventory.is_empty() && output.inventory.contains(&accepts) {
-                output.inventory.transfer(&accepts, &mut input.inventory);
+        if to == ent {
+            let (Ok(mut output), Ok((_, mut input))) =
+                (output_query.get_mut(ent), input_query.get_mut(ent))
+            else {
+                continue;
+            };
+            let item = input.inventory.items.remove(index);
+            output.inventory.push(item.clone(), &limits);
+            transferred.send(ItemTransferred {
+                from: ent,
+                to: ent,
+                item,
+            });
+        } else {
+            let Ok([(_, mut input), (_, mut side_input)]) = input_query.get_many_mut([ent, to])
+            else {
+                continue;
+            };
+            let item = input.inventory.items.remove(index);
+            side_input.inventory.push(item.clone(), &limits);
+            transferred.send(ItemTransferred {
+                from: ent,
+                to,
+                item,
+            });
+        }
+
+        set_status(ent, &mut status, None, &mut stalled);
+        splitter.alternate = !splitter.alternate;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn converter_system(
+    mut query: Query<(
+        Entity,
+        &mut Converter,
+        &mut BlockStatus,
+        Option<&mut components::Input>,
+        Option<&mut Output>,
+        Option<&PowerConsumer>,
+        Option<&mut Generator>,
+        Option<&Temperature>,
+    )>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut produced: EventWriter<ItemProduced>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, mut converter, mut status, mut input, mut output, power, generator, temperature) in
+        query.iter_mut()
+    {
+        let input_stack = converter.input.to_item_stack(converter.rate);
+        let electric = converter.input == Energy::Electric;
+
+        // electric input is drawn from the power network, which scales the speed instead, so
+        // without a network there is nothing to convert
+        let speed = power.map_or(if electric { 0. } else { 1. }, |power| power.satisfaction);
+        let has_input = electric
+            || input
+                .as_ref()
+                .is_some_and(|input| input.inventory.contains(&input_stack));
+        let stall = if temperature.is_some_and(|temperature| temperature.is_overheated()) {
+            Some(StallReason::Overheated)
+        } else if !has_input {
+            Some(StallReason::MissingInput(input_stack.item_type.clone()))
+        } else if speed <= 0. {
+            Some(StallReason::NoPower)
+        } else {
+            None
+        };
+
+        let stopped = stall.is_some();
+        if let Some(mut generator) = generator {
+            generator.output = if stopped {
+                0.
+            } else {
+                converter.output_per_second()
+            };
+        }
+        set_status(ent, &mut status, stall, &mut stalled);
+        if stopped {
+            continue;
+        }
+
+        converter.timer.tick(time.delta().mul_f32(speed));
+        if !converter.timer.just_finished() {
+            continue;
+        }
+
+        if converter.input != Energy::Electric {
+            if let Some(input) = input.as_mut() {
+                input.inventory.remove(&input_stack);
+                consumed.send(ItemConsumed {
+                    entity: ent,
+                    item: input_stack,
+                });
             }
+        }
+
+        let Some(output) = output.as_mut() else {
+            continue;
+        };
+        let mut made = vec![];
+        if converter.output != Energy::Electric && converter.output_per_cycle() > 0 {
+            made.push(
+                converter
+                    .output
+                    .clone()
+                    .to_item_stack(converter.output_per_cycle()),
+            );
+        }
+        if converter.loss_per_cycle() > 0 {
+            made.push(Energy::Thermal.to_item_stack(converter.loss_per_cycle()));
+        }
+        for item in made {
+            output.inventory.push(item.clone(), &limits);
+            produced.send(ItemProduced { entity: ent, item });
+        }
+    }
+}
+
+fn accumulator_system(
+    mut query: Query<(
+        Entity,
+        &mut Accumulator,
+        &mut components::Input,
+        &mut Output,
+        &mut BlockStatus,
+    )>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, mut accumulator, mut input, mut output, mut status) in query.iter_mut() {
+        // electric charge is buffered by the power network instead of moved as items
+        if accumulator.energy == Energy::Electric {
+            set_status(ent, &mut status, None, &mut stalled);
+            continue;
+        }
+        accumulator.timer.tick(time.delta());
+        if !accumulator.timer.just_finished() {
+            continue;
+        }
+
+        let stored = ItemStackType::Energy(accumulator.energy.clone());
+        let room = (accumulator.capacity - accumulator.charge).floor() as u32;
+        let charge = input
+            .inventory
+            .quantity(&stored)
+            .min(accumulator.charge_rate as u32)
+            .min(room);
+        if charge > 0 {
+            input.inventory.remove(&stored.to_item_stack(charge));
+            accumulator.charge += charge as f32;
+        }
+
+        // keep one cycle's worth of discharge in the output for grabbers to take
+        let wanted = (accumulator.discharge_rate as u32)
+            .saturating_sub(output.inventory.quantity(&stored))
+            .min(accumulator.charge.floor() as u32);
+        if wanted > 0 {
+            output.inventory.push(stored.to_item_stack(wanted), &limits);
+            accumulator.charge -= wanted as f32;
+        }
+
+        let stall = if charge > 0 || wanted > 0 {
+            None
+        } else if accumulator.charge <= 0. {
+            Some(StallReason::MissingInput(stored))
+        } else {
+            Some(StallReason::Idle)
+        };
+        set_status(ent, &mut status, stall, &mut stalled);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn centrifuge_system(
+    mut query: Query<(
+        Entity,
+        &mut Centrifuge,
+        &mut components::Input,
+        &mut Output,
+        &mut BlockStatus,
+        Option<&PowerConsumer>,
+        Option<&Temperature>,
+    )>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut produced: EventWriter<ItemProduced>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, mut centrifuge, mut input, mut output, mut status, power, temperature) in
+        query.iter_mut()
+    {
+        // only the natural mix can be separated, already separated isotopes are left alone
+        let feed = input
+            .inventory
+            .items
+            .iter()
+            .find_map(|stack| match &stack.item_type {
+                ItemStackType::Element(element, state, None)
+                    if stack.quantity >= centrifuge.rate
+                        && element.natural_isotopes().is_some() =>
+                {
+                    Some((element.clone(), state.clone()))
+                }
+                _ => None,
+            });
+        let speed = power.map_or(1., |power| power.satisfaction);
+        let stall = if temperature.is_some_and(|temperature| temperature.is_overheated()) {
+            Some(StallReason::Overheated)
+        } else if feed.is_none() {
+            Some(StallReason::Idle)
+        } else if speed <= 0. {
+            Some(StallReason::NoPower)
+        } else {
+            None
+        };
+        set_status(ent, &mut status, stall, &mut stalled);
+        let Some((element, state)) = feed.filter(|_| status.stalled.is_none()) else {
+            continue;
+        };
+
+        centrifuge.timer.tick(time.delta().mul_f32(speed));
+        if !centrifuge.timer.just_finished() {
+            continue;
+        }
+
+        let rate = centrifuge.rate;
+        let fed = element.to_item_stack(state.clone(), rate);
+        input.inventory.remove(&fed);
+        consumed.send(ItemConsumed {
+            entity: ent,
+            item: fed,
+        });
+
+        for (mass, abundance) in element.natural_isotopes().unwrap() {
+            let isotope = ItemStackType::Element(element.clone(), state.clone(), Some(*mass));
+            let amount = rate as f32 * abundance;
+            match centrifuge
+                .separated
+                .iter_mut()
+                .find(|(item_type, _)| *item_type == isotope)
+            {
+                Some((_, separated)) => *separated += amount,
+                None => centrifuge.separated.push((isotope, amount)),
+            }
+        }
+
+        for (item_type, separated) in centrifuge.separated.iter_mut() {
+            if *separated < 1. {
+                continue;
+            }
+            let whole = separated.floor();
+            let item = item_type.to_item_stack(whole as u32);
+            output.inventory.push(item.clone(), &limits);
+            produced.send(ItemProduced { entity: ent, item });
+            *separated -= whole;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn phase_change_system(
+    mut query: Query<(
+        Entity,
+        &mut PhaseChanger,
+        &mut components::Input,
+        &mut Output,
+        &mut BlockStatus,
+        Option<&PowerConsumer>,
+        Option<&mut Temperature>,
+    )>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut produced: EventWriter<ItemProduced>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    for (ent, mut changer, mut input, mut output, mut status, power, mut temperature) in
+        query.iter_mut()
+    {
+        let feed = input
+            .inventory
+            .items
+            .iter()
+            .find_map(|stack| match &stack.item_type {
+                ItemStackType::Element(element, state, mass) if *state == changer.from => element
+                    .phase_change_energy(&changer.from, &changer.to)
+                    .map(|cost| (element.clone(), *mass, cost)),
+                _ => None,
+            });
+
+        let heated = changer.energy == Energy::Thermal;
+        let fuel = feed
+            .as_ref()
+            .map(|(_, _, cost)| Energy::Thermal.to_item_stack(*cost));
+        let speed = match (power, &feed) {
+            (Some(power), Some((_, _, cost))) => power.satisfaction * power.demand / *cost as f32,
+            _ => 1.,
+        };
+        let stall = if temperature
+            .as_ref()
+            .is_some_and(|temperature| temperature.is_overheated())
+        {
+            Some(StallReason::Overheated)
+        } else if feed.is_none() {
+            Some(StallReason::Idle)
+        } else if heated
+            && !fuel
+                .as_ref()
+                .is_some_and(|fuel| input.inventory.contains(fuel))
+        {
+            Some(StallReason::MissingInput(ItemStackType::Energy(
+                Energy::Thermal,
+            )))
+        } else if speed <= 0. {
+            Some(StallReason::NoPower)
         } else {
-            output.inventory.transfer_first(&mut input.inventory);
+            None
+        };
+        let stopped = stall.is_some();
+        set_status(ent, &mut status, stall, &mut stalled);
+        let (Some((element, mass, cost)), Some(fuel)) = (feed, fuel) else {
+            continue;
+        };
+        if stopped {
+            continue;
+        }
+
+        changer.timer.tick(time.delta().mul_f32(speed));
+        if !changer.timer.just_finished() {
+            continue;
+        }
+
+        let fed =
+            ItemStackType::Element(element.clone(), changer.from.clone(), mass).to_item_stack(1);
+        input.inventory.remove(&fed);
+        consumed.send(ItemConsumed {
+            entity: ent,
+            item: fed,
+        });
+        if heated {
+            input.inventory.remove(&fuel);
+            consumed.send(ItemConsumed {
+                entity: ent,
+                item: fuel.clone(),
+            });
+            // burning fuel is never perfectly efficient, the rest warms the machine
+            if let Some(temperature) = temperature.as_mut() {
+                temperature.add_heat(cost as f32 * BURNER_WASTE_HEAT);
+            }
+        }
+
+        let mut made =
+            vec![ItemStackType::Element(element, changer.to.clone(), mass).to_item_stack(1)];
+        if changer.releases_heat() {
+            made.push(fuel);
+        }
+        for item in made {
+            output.inventory.push(item.clone(), &limits);
+            produced.send(ItemProduced { entity: ent, item });
         }
     }
 }
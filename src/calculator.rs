@@ -0,0 +1,414 @@
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+use serde::Serialize;
+
+use crate::{
+    materials::{self, Element, ItemStackType, Reaction},
+    player::{element_picker, enum_dropdown},
+    reactions::ReactionRegistry,
+    ui::not_typing,
+};
+
+pub struct CalculatorPlugin;
+
+impl Plugin for CalculatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Calculator>()
+            .add_system(calculator_hotkey.run_if(not_typing))
+            .add_system(calculator_ui);
+    }
+}
+
+/// Where exported plans are written, relative to the working directory.
+const EXPORT_PATH: &str = "production_plan.json";
+
+#[derive(Resource)]
+pub struct Calculator {
+    pub open: bool,
+    pub target_element: Element,
+    pub target_state: materials::State,
+    /// Units per minute.
+    pub rate: f32,
+    /// Index into the producing reactions of each item, one past the end means it is supplied
+    /// from outside instead.
+    pub choices: HashMap<ItemStackType, usize>,
+    element_search: String,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        Self {
+            open: false,
+            target_element: Element::default(),
+            target_state: materials::State::default(),
+            rate: 60.,
+            choices: HashMap::new(),
+            element_search: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanNode {
+    #[serde(skip)]
+    pub item_type: ItemStackType,
+    pub item: String,
+    /// Units per minute.
+    pub rate: f32,
+    pub reaction: Option<String>,
+    pub machines: f32,
+    /// Names of every reaction that could make this item, see [`Calculator::choices`].
+    pub alternatives: Vec<String>,
+    pub choice: usize,
+    /// Set when the item is already being made further up the tree, so it is treated as
+    /// supplied to stop the loop.
+    pub cycle: bool,
+    pub inputs: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Plan {
+    pub root: PlanNode,
+    /// Units per minute of everything that has to be supplied from outside.
+    pub raw: Vec<(String, f32)>,
+    /// Units per minute of everything made that the chain does not use.
+    pub byproducts: Vec<(String, f32)>,
+}
+
+fn add_rate(totals: &mut Vec<(ItemStackType, f32)>, item: &ItemStackType, rate: f32) {
+    match totals.iter_mut().find(|(other, _)| other == item) {
+        Some((_, total)) => *total += rate,
+        None => totals.push((item.clone(), rate)),
+    }
+}
+
+/// Units of `item` a reaction makes per run, less any it uses up itself.
+fn net_output(reaction: &Reaction, item: &ItemStackType) -> i64 {
+    let made = reaction
+        .output
+        .iter()
+        .filter(|stack| stack.item_type.matches(item))
+        .map(|stack| stack.quantity as i64)
+        .sum::<i64>();
+    let used = reaction
+        .input
+        .iter()
+        .filter(|stack| stack.item_type.matches(item))
+        .map(|stack| stack.quantity as i64)
+        .sum::<i64>();
+    made - used
+}
+
+struct Planner<'a> {
+    reactions: &'a [Reaction],
+    choices: &'a HashMap<ItemStackType, usize>,
+    path: Vec<ItemStackType>,
+    raw: Vec<(ItemStackType, f32)>,
+    byproducts: Vec<(ItemStackType, f32)>,
+}
+
+impl<'a> Planner<'a> {
+    fn node(&mut self, item_type: &ItemStackType, rate: f32) -> PlanNode {
+        let alternatives = self
+            .reactions
+            .iter()
+            .filter(|reaction| net_output(reaction, item_type) > 0)
+            .collect::<Vec<_>>();
+        let cycle = self.path.contains(item_type);
+        let choice = (*self.choices.get(item_type).unwrap_or(&0)).min(alternatives.len());
+
+        let mut node = PlanNode {
+            item_type: item_type.clone(),
+            item: item_type.to_string(),
+            rate,
+            reaction: None,
+            machines: 0.,
+            alternatives: alternatives
+                .iter()
+                .map(|reaction| reaction.name.clone())
+                .collect(),
+            choice,
+            cycle,
+            inputs: vec![],
+        };
+
+        let Some(reaction) = alternatives.get(choice).filter(|_| !cycle) else {
+            add_rate(&mut self.raw, item_type, rate);
+            return node;
+        };
+
+        let runs_per_minute = rate / net_output(reaction, item_type) as f32;
+        node.reaction = Some(reaction.name.clone());
+        node.machines = runs_per_minute * reaction.duration.as_secs_f32() / 60.;
+
+        for stack in reaction.output.iter() {
+            if !stack.item_type.matches(item_type) {
+                add_rate(
+                    &mut self.byproducts,
+                    &stack.item_type,
+                    stack.quantity as f32 * runs_per_minute,
+                );
+            }
+        }
+
+        self.path.push(item_type.clone());
+        for stack in reaction.input.iter() {
+            if stack.item_type.matches(item_type) {
+                continue;
+            }
+            let input = self.node(&stack.item_type, stack.quantity as f32 * runs_per_minute);
+            node.inputs.push(input);
+        }
+        self.path.pop();
+
+        node
+    }
+}
+
+/// Walks the registry back from `target` until everything left is supplied from outside.
+pub fn plan(
+    registry: &ReactionRegistry,
+    choices: &HashMap<ItemStackType, usize>,
+    target: &ItemStackType,
+    rate: f32,
+) -> Plan {
+    let mut planner = Planner {
+        reactions: &registry.reactions,
+        choices,
+        path: vec![],
+        raw: vec![],
+        byproducts: vec![],
+    };
+    let root = planner.node(target, rate);
+
+    // byproducts used further down the chain cover some of the raw inputs
+    let mut raw = planner.raw;
+    let mut byproducts = planner.byproducts;
+    for (item, supplied) in byproducts.iter_mut() {
+        if let Some((_, needed)) = raw.iter_mut().find(|(other, _)| item.matches(other)) {
+            let used = supplied.min(*needed);
+            *supplied -= used;
+            *needed -= used;
+        }
+    }
+    raw.retain(|(_, rate)| *rate > 0.);
+    byproducts.retain(|(_, rate)| *rate > 0.);
+
+    Plan {
+        root,
+        raw: raw
+            .into_iter()
+            .map(|(item, rate)| (item.to_string(), rate))
+            .collect(),
+        byproducts: byproducts
+            .into_iter()
+            .map(|(item, rate)| (item.to_string(), rate))
+            .collect(),
+    }
+}
+
+fn calculator_hotkey(keys: Res<Input<KeyCode>>, mut calculator: ResMut<Calculator>) {
+    if keys.just_pressed(KeyCode::C) {
+        calculator.open = !calculator.open;
+    }
+}
+
+fn show_node(ui: &mut egui::Ui, node: &PlanNode, choices: &mut HashMap<ItemStackType, usize>) {
+    let title = match &node.reaction {
+        Some(reaction) => format!(
+            "{} {:.1}/min, {:.2} x {}",
+            node.item, node.rate, node.machines, reaction
+        ),
+        None if node.cycle => format!("{} {:.1}/min (cycle)", node.item, node.rate),
+        None => format!("{} {:.1}/min (supplied)", node.item, node.rate),
+    };
+
+    egui::CollapsingHeader::new(title)
+        .id_source(ui.next_auto_id())
+        .default_open(true)
+        .show(ui, |ui| {
+            if !node.alternatives.is_empty() {
+                let mut choice = node.choice;
+                egui::ComboBox::from_id_source(ui.next_auto_id())
+                    .selected_text(
+                        node.alternatives
+                            .get(choice)
+                            .map_or("Supplied", |name| name.as_str()),
+                    )
+                    .show_ui(ui, |ui| {
+                        for (i, name) in node.alternatives.iter().enumerate() {
+                            ui.selectable_value(&mut choice, i, name);
+                        }
+                        ui.selectable_value(&mut choice, node.alternatives.len(), "Supplied");
+                    });
+                if choice != node.choice {
+                    choices.insert(node.item_type.clone(), choice);
+                }
+            }
+            for input in node.inputs.iter() {
+                show_node(ui, input, choices);
+            }
+        });
+}
+
+fn calculator_ui(
+    mut egui_ctx: EguiContexts,
+    mut calculator: ResMut<Calculator>,
+    registry: Res<ReactionRegistry>,
+) {
+    if !calculator.open {
+        return;
+    }
+    let calculator = &mut *calculator;
+
+    let target = ItemStackType::Element(
+        calculator.target_element.clone(),
+        calculator.target_state.clone(),
+        None,
+    );
+    let plan = plan(&registry, &calculator.choices, &target, calculator.rate);
+
+    egui::Window::new("Production Calculator")
+        .open(&mut calculator.open)
+        .default_width(400.)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                element_picker(
+                    ui,
+                    "calculator-el".to_string(),
+                    &mut calculator.element_search,
+                    &mut calculator.target_element,
+                );
+                enum_dropdown::<materials::State>(
+                    ui,
+                    "calculator-st".to_string(),
+                    "State",
+                    &mut calculator.target_state,
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Per minute");
+                ui.add(
+                    egui::DragValue::new(&mut calculator.rate)
+                        .speed(1.)
+                        .clamp_range(0.1..=100000.),
+                );
+                if ui.button("Export JSON").clicked() {
+                    match serde_json::to_string_pretty(&plan) {
+                        Ok(json) => match std::fs::write(EXPORT_PATH, json) {
+                            Ok(()) => info!("Exported production plan to {}", EXPORT_PATH),
+                            Err(err) => error!("Could not write {}: {}", EXPORT_PATH, err),
+                        },
+                        Err(err) => error!("Could not serialise production plan: {}", err),
+                    }
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(300.)
+                .show(ui, |ui| {
+                    show_node(ui, &plan.root, &mut calculator.choices);
+                });
+
+            ui.separator();
+            ui.heading("Raw inputs");
+            for (item, rate) in plan.raw.iter() {
+                ui.label(format!("{} {:.1}/min", item, rate));
+            }
+            ui.heading("Byproducts");
+            for (item, rate) in plan.byproducts.iter() {
+                ui.label(format!("{} {:.1}/min", item, rate));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::materials::State;
+
+    fn solid(element: Element) -> ItemStackType {
+        ItemStackType::Element(element, State::Solid, None)
+    }
+
+    fn reaction(name: &str, input: &[(Element, u32)], output: &[(Element, u32)]) -> Reaction {
+        Reaction {
+            name: name.to_string(),
+            input: input
+                .iter()
+                .map(|(element, quantity)| element.to_item_stack(State::Solid, *quantity))
+                .collect(),
+            output: output
+                .iter()
+                .map(|(element, quantity)| element.to_item_stack(State::Solid, *quantity))
+                .collect(),
+            duration: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn cycles_are_supplied_from_outside() {
+        let registry = ReactionRegistry {
+            reactions: vec![
+                reaction(
+                    "Copper to iron",
+                    &[(Element::Copper, 1)],
+                    &[(Element::Iron, 1)],
+                ),
+                reaction(
+                    "Iron to copper",
+                    &[(Element::Iron, 1)],
+                    &[(Element::Copper, 1)],
+                ),
+            ],
+        };
+
+        let plan = plan(&registry, &HashMap::new(), &solid(Element::Iron), 10.);
+
+        assert_eq!(plan.root.reaction.as_deref(), Some("Copper to iron"));
+        assert_eq!(plan.root.machines, 10.);
+        let copper = &plan.root.inputs[0];
+        assert_eq!(copper.reaction.as_deref(), Some("Iron to copper"));
+        assert!(copper.inputs[0].cycle);
+        assert!(copper.inputs[0].reaction.is_none());
+        assert_eq!(plan.raw, vec![(solid(Element::Iron).to_string(), 10.)]);
+    }
+
+    #[test]
+    fn choices_pick_between_alternatives() {
+        let registry = ReactionRegistry {
+            reactions: vec![
+                reaction(
+                    "From copper",
+                    &[(Element::Copper, 2)],
+                    &[(Element::Iron, 1)],
+                ),
+                reaction(
+                    "From tin",
+                    &[(Element::Tin, 1)],
+                    &[(Element::Iron, 2), (Element::Lead, 1)],
+                ),
+            ],
+        };
+        let target = solid(Element::Iron);
+
+        let default = plan(&registry, &HashMap::new(), &target, 4.);
+        assert_eq!(default.root.alternatives, vec!["From copper", "From tin"]);
+        assert_eq!(default.root.choice, 0);
+        assert_eq!(default.raw, vec![(solid(Element::Copper).to_string(), 8.)]);
+        assert!(default.byproducts.is_empty());
+
+        let choices = HashMap::from([(target.clone(), 1)]);
+        let chosen = plan(&registry, &choices, &target, 4.);
+        assert_eq!(chosen.root.reaction.as_deref(), Some("From tin"));
+        assert_eq!(chosen.root.machines, 2.);
+        assert_eq!(chosen.raw, vec![(solid(Element::Tin).to_string(), 2.)]);
+        assert_eq!(
+            chosen.byproducts,
+            vec![(solid(Element::Lead).to_string(), 2.)]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+// work in progress, most of the generation is still commented out
+#![allow(unused)]
+
 use bevy::{prelude::*, render::render_resource::PrimitiveTopology};
 use bevy_prototype_debug_lines::DebugShapes;
 use bevy_vox_mesh::VoxMeshPlugin;
@@ -335,12 +338,8 @@ fn spawn_wireframes(city_blocks: Res<CityBlocks>, mut debug_shapes: ResMut<Debug
                     debug_shapes
                         .line()
                         .start_end(
-                            Vec3::new(
-                                last_point.x as f32 as f32,
-                                slot.height as f32,
-                                last_point.y as f32,
-                            ),
-                            Vec3::new(point.x as f32 as f32, slot.height as f32, point.y as f32),
+                            Vec3::new(last_point.x, slot.height as f32, last_point.y),
+                            Vec3::new(point.x, slot.height as f32, point.y),
                         )
                         .color(Color::rgb_u8(0, 0, 201));
                     last_point = point;
@@ -1,10 +1,11 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
 use crate::{
     blocks::BlockType,
-    materials::{Inventory, ItemStack, Reaction},
+    events::StallReason,
+    materials::{Element, Energy, Inventory, ItemStack, ItemStackType, Reaction, State},
     player,
 };
 
@@ -15,7 +16,10 @@ impl Plugin for ComponentPlugin {
         app.register_type::<Block>()
             .register_type::<Input>()
             .register_type::<Output>()
-            .register_type::<Process>();
+            .register_type::<Process>()
+            .register_type::<Converter>()
+            .register_type::<Accumulator>()
+            .register_type::<PhaseChanger>();
     }
 }
 
@@ -35,14 +39,38 @@ impl Default for Conveyor {
     }
 }
 
-#[derive(Component, Default)]
-pub struct Splitter;
+/// Sends items of the `filter` element straight on and everything else out of its right side.
+/// Without a filter it alternates between the two.
+#[derive(Component)]
+pub struct Splitter {
+    pub filter: Option<Element>,
+    pub timer: Timer,
+    pub alternate: bool,
+}
+
+impl Default for Splitter {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            timer: Timer::new(Duration::from_millis(1000), TimerMode::Repeating),
+            alternate: false,
+        }
+    }
+}
 
 #[derive(Component, Default)]
 pub struct Storage;
 
+/// Moves [`Grabber::RATE`] items a second at full power, `progress` builds up towards the next
+/// move.
 #[derive(Component, Default)]
-pub struct Grabber;
+pub struct Grabber {
+    pub progress: f32,
+}
+
+impl Grabber {
+    pub const RATE: f32 = 2.;
+}
 
 #[derive(Component)]
 pub struct BlockClicked {}
@@ -64,6 +92,64 @@ pub struct Process {
     pub timer: Timer,
 }
 
+/// Why the block isn't working, if it isn't.
+#[derive(Component, Default)]
+pub struct BlockStatus {
+    pub stalled: Option<StallReason>,
+}
+
+/// Seconds spent working and stalled for each reason, over about the last
+/// [`Utilisation::WINDOW`] seconds. Time spent idle isn't counted.
+#[derive(Component, Default)]
+pub struct Utilisation {
+    pub working: f32,
+    pub stalled: HashMap<StallReason, f32>,
+}
+
+impl Utilisation {
+    pub const WINDOW: f32 = 60.;
+
+    pub fn record(&mut self, stall: Option<&StallReason>, seconds: f32) {
+        match stall {
+            Some(StallReason::Idle) => return,
+            Some(reason) => *self.stalled.entry(reason.clone()).or_default() += seconds,
+            None => self.working += seconds,
+        }
+        // scale everything down rather than keep a history, older time fades out
+        let total = self.total();
+        if total > Self::WINDOW {
+            let scale = Self::WINDOW / total;
+            self.working *= scale;
+            self.stalled.values_mut().for_each(|time| *time *= scale);
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.working + self.stalled.values().sum::<f32>()
+    }
+
+    pub fn working_fraction(&self) -> f32 {
+        self.fraction(self.working)
+    }
+
+    /// The reason the block was stalled longest, and for what fraction of the time.
+    pub fn worst_stall(&self) -> Option<(&StallReason, f32)> {
+        self.stalled
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(reason, time)| (reason, self.fraction(*time)))
+    }
+
+    fn fraction(&self, time: f32) -> f32 {
+        let total = self.total();
+        if total > 0. {
+            time / total
+        } else {
+            0.
+        }
+    }
+}
+
 #[derive(Component, Default, Reflect)]
 pub struct Source {
     pub source: Option<ItemStack>,
@@ -72,6 +158,17 @@ pub struct Source {
     pub inventory: Inventory,
 }
 
+impl Source {
+    pub fn new(source: ItemStack, fequency: Duration) -> Self {
+        Self {
+            source: Some(source),
+            fequency,
+            timer: Timer::new(fequency, TimerMode::Repeating),
+            inventory: Inventory::default(),
+        }
+    }
+}
+
 impl Process {
     pub fn set_reaction(&mut self, reaction: &Reaction) {
         self.reaction = Some(reaction.clone());
@@ -79,8 +176,219 @@ impl Process {
     }
 }
 
+/// Turns `rate` units of one energy form into another every cycle. Whatever is lost to
+/// `efficiency` comes out as `Energy::Thermal`.
+#[derive(Component, Reflect)]
+pub struct Converter {
+    pub input: Energy,
+    pub output: Energy,
+    pub efficiency: f32,
+    pub rate: u32,
+    pub timer: Timer,
+}
+
+impl Converter {
+    pub fn new(input: Energy, output: Energy, efficiency: f32) -> Self {
+        Self {
+            input,
+            output,
+            efficiency,
+            rate: 10,
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+        }
+    }
+
+    pub fn turbine() -> Self {
+        Self::new(Energy::Thermal, Energy::Mechanical, 0.4)
+    }
+
+    pub fn generator() -> Self {
+        Self::new(Energy::Mechanical, Energy::Electric, 0.9)
+    }
+
+    pub fn heater() -> Self {
+        Self::new(Energy::Electric, Energy::Thermal, 1.0)
+    }
+
+    pub fn photovoltaic() -> Self {
+        Self::new(Energy::Radiant, Energy::Electric, 0.2)
+    }
+
+    pub fn motor() -> Self {
+        Self::new(Energy::Electric, Energy::Mechanical, 0.85)
+    }
+
+    pub fn output_per_cycle(&self) -> u32 {
+        (self.rate as f32 * self.efficiency).floor() as u32
+    }
+
+    pub fn loss_per_cycle(&self) -> u32 {
+        self.rate - self.output_per_cycle()
+    }
+
+    /// Rate of `input` drawn while running, in units per second.
+    pub fn input_per_second(&self) -> f32 {
+        self.rate as f32 / self.timer.duration().as_secs_f32()
+    }
+
+    /// Rate of `output` produced while running, in units per second.
+    pub fn output_per_second(&self) -> f32 {
+        self.output_per_cycle() as f32 / self.timer.duration().as_secs_f32()
+    }
+}
+
+/// Stores one form of `Energy`. Charges from energy items in its input and tops its output
+/// back up from storage, both limited per cycle. Electric accumulators also buffer their
+/// power network.
+#[derive(Component, Reflect)]
+pub struct Accumulator {
+    pub energy: Energy,
+    pub charge: f32,
+    pub capacity: f32,
+    pub charge_rate: f32,
+    pub discharge_rate: f32,
+    pub timer: Timer,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            energy: Energy::Electric,
+            charge: 0.,
+            capacity: 5000.,
+            charge_rate: 50.,
+            discharge_rate: 50.,
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+        }
+    }
+}
+
+impl Accumulator {
+    pub fn percent(&self) -> f32 {
+        if self.capacity <= 0. {
+            return 0.;
+        }
+        self.charge / self.capacity
+    }
+}
+
+/// Spins `rate` units of a natural element out of its input each cycle and sorts them into
+/// isotopes by mass. Fractions of an isotope carry over in `separated` until a whole unit
+/// is ready.
+#[derive(Component)]
+pub struct Centrifuge {
+    pub rate: u32,
+    pub timer: Timer,
+    pub separated: Vec<(ItemStackType, f32)>,
+}
+
+impl Default for Centrifuge {
+    fn default() -> Self {
+        Self {
+            rate: 10,
+            timer: Timer::new(Duration::from_secs(2), TimerMode::Repeating),
+            separated: vec![],
+        }
+    }
+}
+
+/// Moves one unit of an element from `from` to `to` each cycle. Heating machines burn
+/// `Energy::Thermal` from their input, cooling machines run on the power network and dump
+/// the heat they pull out into their output. Either way the cost comes from
+/// `Element::phase_change_energy`.
+#[derive(Component, Reflect)]
+pub struct PhaseChanger {
+    pub from: State,
+    pub to: State,
+    pub energy: Energy,
+    pub timer: Timer,
+}
+
+impl PhaseChanger {
+    pub fn new(from: State, to: State, energy: Energy) -> Self {
+        Self {
+            from,
+            to,
+            energy,
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+        }
+    }
+
+    pub fn melter() -> Self {
+        Self::new(State::Solid, State::Liquid, Energy::Thermal)
+    }
+
+    pub fn boiler() -> Self {
+        Self::new(State::Liquid, State::Gas, Energy::Thermal)
+    }
+
+    pub fn condenser() -> Self {
+        Self::new(State::Gas, State::Liquid, Energy::Electric)
+    }
+
+    pub fn freezer() -> Self {
+        Self::new(State::Liquid, State::Solid, Energy::Electric)
+    }
+
+    pub fn plasma_torch() -> Self {
+        Self::new(State::Gas, State::Plasma, Energy::Electric)
+    }
+
+    /// Whether the change gives heat off rather than taking it in.
+    pub fn releases_heat(&self) -> bool {
+        matches!(
+            (&self.from, &self.to),
+            (State::Liquid, State::Solid)
+                | (State::Gas, State::Liquid)
+                | (State::Plasma, State::Gas)
+        )
+    }
+}
+
 #[derive(Component, Reflect)]
 pub struct Block {
     pub block_type: BlockType,
     pub direction: player::Direction,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_ignores_idle_time() {
+        let mut utilisation = Utilisation::default();
+        utilisation.record(None, 3.);
+        utilisation.record(Some(&StallReason::NoPower), 1.);
+        utilisation.record(Some(&StallReason::Idle), 10.);
+
+        assert_eq!(utilisation.total(), 4.);
+        assert_eq!(utilisation.working_fraction(), 0.75);
+        assert_eq!(
+            utilisation.worst_stall(),
+            Some((&StallReason::NoPower, 0.25))
+        );
+    }
+
+    #[test]
+    fn record_fades_out_old_time() {
+        let mut utilisation = Utilisation::default();
+        utilisation.record(None, Utilisation::WINDOW);
+        utilisation.record(Some(&StallReason::OutputFull), Utilisation::WINDOW);
+
+        assert_eq!(utilisation.total(), Utilisation::WINDOW);
+        assert_eq!(utilisation.working_fraction(), 0.5);
+
+        utilisation.record(Some(&StallReason::OutputFull), Utilisation::WINDOW);
+        assert_eq!(utilisation.total(), Utilisation::WINDOW);
+        assert_eq!(utilisation.working_fraction(), 0.25);
+    }
+
+    #[test]
+    fn nothing_recorded_is_not_working() {
+        let utilisation = Utilisation::default();
+
+        assert_eq!(utilisation.working_fraction(), 0.);
+        assert_eq!(utilisation.worst_stall(), None);
+    }
+}
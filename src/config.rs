@@ -0,0 +1,98 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+/// A config file in RON, applied to its `Target` resource every time it loads.
+pub trait RonAsset: Asset + DeserializeOwned {
+    type Target: Resource;
+
+    fn apply(&self, target: &mut Self::Target);
+}
+
+/// Loads `T` from `path` at startup and keeps it applied as the file is hot reloaded. `extension`
+/// is what tells the asset server which files are a `T`.
+pub struct RonAssetPlugin<T> {
+    path: &'static str,
+    extension: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetPlugin<T> {
+    pub fn new(path: &'static str, extension: &'static str) -> Self {
+        Self {
+            path,
+            extension,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RonAsset> Plugin for RonAssetPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let path = self.path;
+        app.add_asset::<T>()
+            .add_asset_loader(RonLoader::<T> {
+                extensions: [self.extension],
+                marker: PhantomData,
+            })
+            .add_startup_system(
+                move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(RonHandle::<T>(asset_server.load(path)));
+                },
+            )
+            .add_system(apply_ron_asset::<T>);
+    }
+}
+
+pub struct RonLoader<T> {
+    extensions: [&'static str; 1],
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: RonAsset> AssetLoader for RonLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = ron::de::from_bytes::<T>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+/// Keeps the config asset loaded so it can be hot reloaded.
+#[derive(Resource)]
+struct RonHandle<T: RonAsset>(Handle<T>);
+
+fn apply_ron_asset<T: RonAsset>(
+    mut events: EventReader<AssetEvent<T>>,
+    assets: Res<Assets<T>>,
+    handle: Res<RonHandle<T>>,
+    mut target: ResMut<T::Target>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle: loaded } | AssetEvent::Modified { handle: loaded }) =
+            event
+        else {
+            continue;
+        };
+        if *loaded != handle.0 {
+            continue;
+        }
+        if let Some(asset) = assets.get(loaded) {
+            asset.apply(&mut target);
+        }
+    }
+}
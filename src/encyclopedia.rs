@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    materials::{self, Element, ItemStackType, Reaction, StackLimits},
+    player::{element_picker, enum_dropdown},
+    reactions::ReactionRegistry,
+    ui::not_typing,
+};
+
+pub struct EncyclopediaPlugin;
+
+impl Plugin for EncyclopediaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Encyclopedia>()
+            .add_system(encyclopedia_hotkey.run_if(not_typing))
+            .add_system(encyclopedia_ui);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Encyclopedia {
+    pub open: bool,
+    search: String,
+    element_search: String,
+    lookup_element: Element,
+    lookup_state: materials::State,
+    selected: Option<ItemStackType>,
+}
+
+/// Whether `item` shows up for the search text, by name or by symbol.
+fn item_matches(item: &ItemStackType, search: &str) -> bool {
+    search.is_empty()
+        || format!("{}", item).to_lowercase().contains(search)
+        || format!("{:?}", item).to_lowercase().contains(search)
+}
+
+fn produces(reaction: &Reaction, item: &ItemStackType) -> bool {
+    reaction
+        .output
+        .iter()
+        .any(|stack| stack.item_type.matches(item) || item.matches(&stack.item_type))
+}
+
+fn consumes(reaction: &Reaction, item: &ItemStackType) -> bool {
+    reaction
+        .input
+        .iter()
+        .any(|stack| item.matches(&stack.item_type) || stack.item_type.matches(item))
+}
+
+fn encyclopedia_hotkey(keys: Res<Input<KeyCode>>, mut encyclopedia: ResMut<Encyclopedia>) {
+    if keys.just_pressed(KeyCode::E) {
+        encyclopedia.open = !encyclopedia.open;
+    }
+}
+
+fn encyclopedia_ui(
+    mut egui_ctx: EguiContexts,
+    mut encyclopedia: ResMut<Encyclopedia>,
+    registry: Res<ReactionRegistry>,
+    limits: Res<StackLimits>,
+) {
+    if !encyclopedia.open {
+        return;
+    }
+    let encyclopedia = &mut *encyclopedia;
+
+    let mut items: Vec<ItemStackType> = vec![];
+    for reaction in registry.reactions.iter() {
+        for stack in reaction.input.iter().chain(reaction.output.iter()) {
+            if !items.contains(&stack.item_type) {
+                items.push(stack.item_type.clone());
+            }
+        }
+    }
+
+    egui::Window::new("Encyclopedia")
+        .open(&mut encyclopedia.open)
+        .default_width(500.)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Search");
+                ui.text_edit_singleline(&mut encyclopedia.search);
+            });
+            ui.horizontal(|ui| {
+                element_picker(
+                    ui,
+                    "encyclopedia-el".to_string(),
+                    &mut encyclopedia.element_search,
+                    &mut encyclopedia.lookup_element,
+                );
+                enum_dropdown::<materials::State>(
+                    ui,
+                    "encyclopedia-st".to_string(),
+                    "State",
+                    &mut encyclopedia.lookup_state,
+                );
+                if ui.button("Look up").clicked() {
+                    encyclopedia.selected = Some(ItemStackType::Element(
+                        encyclopedia.lookup_element.clone(),
+                        encyclopedia.lookup_state.clone(),
+                        None,
+                    ));
+                }
+            });
+            ui.separator();
+
+            let search = encyclopedia.search.trim().to_lowercase();
+            ui.columns(2, |columns| {
+                columns[0].heading("Items");
+                egui::ScrollArea::vertical()
+                    .id_source("encyclopedia_items")
+                    .max_height(200.)
+                    .show(&mut columns[0], |ui| {
+                        for item in items.iter().filter(|item| item_matches(item, &search)) {
+                            ui.selectable_value(
+                                &mut encyclopedia.selected,
+                                Some(item.clone()),
+                                item.to_string(),
+                            );
+                        }
+                    });
+
+                columns[1].heading("Reactions");
+                egui::ScrollArea::vertical()
+                    .id_source("encyclopedia_reactions")
+                    .max_height(200.)
+                    .show(&mut columns[1], |ui| {
+                        for reaction in registry.reactions.iter().filter(|reaction| {
+                            reaction.name.to_lowercase().contains(&search)
+                                || reaction
+                                    .input
+                                    .iter()
+                                    .chain(reaction.output.iter())
+                                    .any(|stack| item_matches(&stack.item_type, &search))
+                        }) {
+                            ui.label(reaction.to_string());
+                        }
+                    });
+            });
+
+            let Some(selected) = &encyclopedia.selected else {
+                return;
+            };
+            ui.separator();
+            ui.heading(selected.to_string());
+            if let Some(element) = selected.element() {
+                ui.label(format!(
+                    "{:?} ({}), atomic number {}, period {}, group {}, {:?}-block, {:?}",
+                    element,
+                    element.symbol(),
+                    element.atomic_number(),
+                    element.period(),
+                    element
+                        .group()
+                        .map_or("-".to_string(), |group| group.to_string()),
+                    element.block(),
+                    element.category(),
+                ));
+                if let Some((melting, boiling)) = element.phase_points() {
+                    ui.label(format!("Melts at {}K, boils at {}K", melting, boiling));
+                }
+            }
+            ui.label(format!("Stack limit {}", limits.limit(selected)));
+
+            ui.collapsing("Produced by", |ui| {
+                for reaction in registry
+                    .reactions
+                    .iter()
+                    .filter(|reaction| produces(reaction, selected))
+                {
+                    ui.label(reaction.to_string());
+                }
+            });
+            ui.collapsing("Consumed by", |ui| {
+                for reaction in registry
+                    .reactions
+                    .iter()
+                    .filter(|reaction| consumes(reaction, selected))
+                {
+                    ui.label(reaction.to_string());
+                }
+            });
+        });
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{
+    blocks::BlockType,
+    materials::{ItemStack, ItemStackType, Reaction},
+};
+
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ItemProduced>()
+            .add_event::<ItemConsumed>()
+            .add_event::<ItemTransferred>()
+            .add_event::<ReactionCompleted>()
+            .add_event::<BlockPlaced>()
+            .add_event::<BlockRemoved>()
+            .add_event::<BlockStalled>()
+            .add_system(log_events);
+    }
+}
+
+/// New items entered the factory, made by a machine, dug up by an extractor or left by decay.
+pub struct ItemProduced {
+    pub entity: Entity,
+    pub item: ItemStack,
+}
+
+/// Items were used up by a block, as a reaction input, fuel or research, or by decaying.
+pub struct ItemConsumed {
+    pub entity: Entity,
+    pub item: ItemStack,
+}
+
+/// Items or fluid moved out of one block and into another, or through a block when `from` and `to` match.
+pub struct ItemTransferred {
+    pub from: Entity,
+    pub to: Entity,
+    pub item: ItemStack,
+}
+
+pub struct ReactionCompleted {
+    pub entity: Entity,
+    pub reaction: Reaction,
+}
+
+pub struct BlockPlaced {
+    pub entity: Entity,
+    pub block_type: BlockType,
+}
+
+/// Sent before the entity is despawned, so it can still be looked up this frame.
+pub struct BlockRemoved {
+    pub entity: Entity,
+    pub block_type: BlockType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StallReason {
+    /// Nothing to do, such as a furnace without a reaction.
+    Idle,
+    MissingInput(ItemStackType),
+    OutputFull,
+    Overheated,
+    NoPower,
+}
+
+/// A block stopped working, sent once when it stops rather than every frame it stays stopped.
+pub struct BlockStalled {
+    pub entity: Entity,
+    pub reason: StallReason,
+}
+
+fn log_events(
+    mut transferred: EventReader<ItemTransferred>,
+    mut completed: EventReader<ReactionCompleted>,
+    mut placed: EventReader<BlockPlaced>,
+    mut removed: EventReader<BlockRemoved>,
+    mut stalled: EventReader<BlockStalled>,
+) {
+    for event in transferred.iter() {
+        debug!("{:?} moved {} to {:?}", event.from, event.item, event.to);
+    }
+    for event in completed.iter() {
+        debug!("{:?} completed {}", event.entity, event.reaction);
+    }
+    for event in placed.iter() {
+        debug!("Placed {:?} {:?}", event.block_type, event.entity);
+    }
+    for event in removed.iter() {
+        debug!("Removed {:?} {:?}", event.block_type, event.entity);
+    }
+    for event in stalled.iter() {
+        debug!("{:?} stalled: {:?}", event.entity, event.reason);
+    }
+}
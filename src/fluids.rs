@@ -0,0 +1,609 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashMap};
+
+use crate::{
+    blocks::set_status,
+    components::{self, Block, BlockStatus, Conveyor, Output, Splitter, Storage},
+    events::{BlockStalled, ItemTransferred, StallReason},
+    grid::{BlockGrid, RemoveBlockEvent},
+    heat::{Temperature, AMBIENT_TEMPERATURE},
+    materials::{ItemStackType, StackLimits, State},
+    power::PowerConsumer,
+    time_controls::simulation_running,
+};
+
+pub struct FluidsPlugin;
+
+impl Plugin for FluidsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FluidTimer(Timer::new(
+            Duration::from_millis(250),
+            TimerMode::Repeating,
+        )))
+        .init_resource::<PipeNetworks>()
+        .add_system(fluid_flow_system.run_if(simulation_running))
+        .add_system(
+            pipe_io_system
+                .after(fluid_flow_system)
+                .run_if(simulation_running),
+        )
+        .add_system(
+            compressor_system
+                .after(fluid_flow_system)
+                .run_if(simulation_running),
+        )
+        .add_system(
+            pipe_network_system
+                .after(compressor_system)
+                .run_if(simulation_running),
+        );
+    }
+}
+
+/// Most a pipe moves into or out of a neighbouring machine per fluid tick.
+const PIPE_IO_RATE: u32 = 5;
+/// Most that flows between two connected segments per fluid tick.
+const FLOW_RATE: u32 = 20;
+/// How many times its volume in moles a segment can be packed with gas.
+const GAS_COMPRESSION: u32 = 10;
+/// Molar gas constant. With volumes in litres, nRT/V comes out in kilopascals.
+const GAS_CONSTANT: f32 = 8.314;
+
+#[derive(Resource)]
+struct FluidTimer(Timer);
+
+#[derive(Component, Default)]
+pub struct Pipe;
+
+#[derive(Component)]
+pub struct Tank {
+    /// Pressure in kilopascals the tank bursts past.
+    pub pressure_rating: f32,
+}
+
+impl Default for Tank {
+    fn default() -> Self {
+        Self {
+            pressure_rating: 5000.,
+        }
+    }
+}
+
+/// Joins the segments either side of it into one network while open.
+#[derive(Component)]
+pub struct Valve {
+    pub open: bool,
+}
+
+impl Default for Valve {
+    fn default() -> Self {
+        Self { open: true }
+    }
+}
+
+/// Pumps gas from the segment behind it into the one in front, whatever their pressures.
+#[derive(Component)]
+pub struct Compressor {
+    pub rate: u32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self { rate: 10 }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PipeNetwork {
+    pub segments: Vec<Entity>,
+    /// Moles of gas or plasma held across the network, liquids take no part in pressure.
+    pub moles: f32,
+    /// Total volume of the network in litres.
+    pub volume: f32,
+    pub temperature: f32,
+    /// In kilopascals.
+    pub pressure: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct PipeNetworks {
+    pub networks: Vec<PipeNetwork>,
+}
+
+impl PipeNetworks {
+    pub fn get(&self, storage: &FluidStorage) -> Option<&PipeNetwork> {
+        storage.network.and_then(|i| self.networks.get(i))
+    }
+}
+
+/// Holds a single fluid. Connected segments level their fill towards each other. `capacity`
+/// is the segment's volume in litres, which gases can be compressed past.
+#[derive(Component)]
+pub struct FluidStorage {
+    pub fluid: Option<ItemStackType>,
+    pub amount: u32,
+    pub capacity: u32,
+    pub network: Option<usize>,
+}
+
+impl FluidStorage {
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            fluid: None,
+            amount: 0,
+            capacity,
+            network: None,
+        }
+    }
+
+    pub fn max_amount(&self, fluid: &ItemStackType) -> u32 {
+        if fluid.is_gas() {
+            self.capacity * GAS_COMPRESSION
+        } else {
+            self.capacity
+        }
+    }
+
+    pub fn fill(&self) -> f32 {
+        if self.capacity == 0 {
+            return 0.;
+        }
+        self.amount as f32 / self.capacity as f32
+    }
+
+    pub fn accepts(&self, fluid: &ItemStackType) -> bool {
+        fluid.is_fluid()
+            && self.amount < self.max_amount(fluid)
+            && (self.fluid.is_none() || self.fluid.as_ref() == Some(fluid))
+    }
+
+    /// Adds up to `amount` of `fluid`, returning how much fit.
+    pub fn fill_with(&mut self, fluid: &ItemStackType, amount: u32) -> u32 {
+        if !self.accepts(fluid) {
+            return 0;
+        }
+        let added = amount.min(self.max_amount(fluid) - self.amount);
+        self.fluid = Some(fluid.clone());
+        self.amount += added;
+        added
+    }
+
+    /// Takes up to `amount` out, returning the fluid and how much was taken.
+    pub fn drain(&mut self, amount: u32) -> Option<(ItemStackType, u32)> {
+        let fluid = self.fluid.clone()?;
+        let taken = amount.min(self.amount);
+        self.amount -= taken;
+        if self.amount == 0 {
+            self.fluid = None;
+        }
+        Some((fluid, taken))
+    }
+}
+
+impl ItemStackType {
+    /// Liquids, gases and plasmas, which only travel through pipes unless containerised.
+    pub fn is_fluid(&self) -> bool {
+        matches!(
+            self,
+            ItemStackType::Element(_, state, _) | ItemStackType::Compound(_, state)
+                if *state != State::Solid
+        )
+    }
+
+    /// Gases and plasmas, which are compressible and build up pressure.
+    pub fn is_gas(&self) -> bool {
+        matches!(
+            self,
+            ItemStackType::Element(_, State::Gas | State::Plasma, _)
+                | ItemStackType::Compound(_, State::Gas | State::Plasma)
+        )
+    }
+
+    pub fn is_conveyable(&self) -> bool {
+        !self.is_fluid()
+    }
+}
+
+/// Every segment that can currently pass fluid on, leaving out closed valves.
+fn is_open(
+    segment_query: &Query<(Entity, &Aabb, &GlobalTransform, Option<&Valve>), With<FluidStorage>>,
+    segment: Entity,
+) -> bool {
+    segment_query
+        .get(segment)
+        .is_ok_and(|(_, _, _, valve)| valve.map(|valve| valve.open).unwrap_or(true))
+}
+
+/// The open segments touching `segment`.
+fn connected_segments(
+    segment_query: &Query<(Entity, &Aabb, &GlobalTransform, Option<&Valve>), With<FluidStorage>>,
+    block_grid: &BlockGrid,
+    segment: Entity,
+) -> Vec<Entity> {
+    let Ok((_, aabb, trans, _)) = segment_query.get(segment) else {
+        return vec![];
+    };
+    block_grid
+        .neighbours(segment, (aabb, trans))
+        .into_iter()
+        .filter(|next| is_open(segment_query, *next))
+        .collect()
+}
+
+fn fluid_flow_system(
+    mut timer: ResMut<FluidTimer>,
+    time: Res<Time>,
+    segment_query: Query<(Entity, &Aabb, &GlobalTransform, Option<&Valve>), With<FluidStorage>>,
+    mut storage_query: Query<&mut FluidStorage>,
+    block_grid: Res<BlockGrid>,
+    mut transferred: EventWriter<ItemTransferred>,
+) {
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    let mut pairs = segment_query
+        .iter()
+        .filter(|(ent, _, _, _)| is_open(&segment_query, *ent))
+        .flat_map(|(a, _, _, _)| {
+            connected_segments(&segment_query, &block_grid, a)
+                .into_iter()
+                .map(move |b| (a.min(b), a.max(b)))
+        })
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs.dedup();
+
+    for (a, b) in pairs {
+        let Ok([mut a_storage, mut b_storage]) = storage_query.get_many_mut([a, b]) else {
+            continue;
+        };
+
+        // flow from the fuller segment towards the emptier one until their fill matches
+        let (from_ent, to_ent, from, to) = if a_storage.fill() >= b_storage.fill() {
+            (a, b, &mut a_storage, &mut b_storage)
+        } else {
+            (b, a, &mut b_storage, &mut a_storage)
+        };
+        let Some(fluid) = from.fluid.clone() else {
+            continue;
+        };
+        if !to.accepts(&fluid) {
+            continue;
+        }
+
+        let total = from.amount + to.amount;
+        let level = total * to.capacity / (from.capacity + to.capacity);
+        let amount = level.saturating_sub(to.amount).min(FLOW_RATE);
+        if amount == 0 {
+            continue;
+        }
+        if let Some((fluid, taken)) = from.drain(amount) {
+            let filled = to.fill_with(&fluid, taken);
+            transferred.send(ItemTransferred {
+                from: from_ent,
+                to: to_ent,
+                item: fluid.to_item_stack(filled),
+            });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn pipe_io_system(
+    timer: Res<FluidTimer>,
+    mut pipe_query: Query<(Entity, &Aabb, &GlobalTransform, &Block, &mut FluidStorage), With<Pipe>>,
+    // belts, splitters and storage only carry fluids in containers, so pipes don't feed them
+    mut input_query: Query<
+        &mut components::Input,
+        (Without<Conveyor>, Without<Splitter>, Without<Storage>),
+    >,
+    mut output_query: Query<&mut Output>,
+    block_grid: Res<BlockGrid>,
+    limits: Res<StackLimits>,
+    mut transferred: EventWriter<ItemTransferred>,
+) {
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    for (pipe, aabb, trans, block, mut storage) in pipe_query.iter_mut() {
+        // pipes pull from the block behind them and feed the block they point at, like grabbers
+        let output = block_grid
+            .next_in_direction((aabb, trans), block.direction.reverse())
+            .and_then(|from| Some((from, output_query.get_mut(from).ok()?)));
+        if let Some((from, mut output)) = output {
+            let stack = output
+                .inventory
+                .items
+                .iter_mut()
+                .find(|stack| match &stack.item_type {
+                    ItemStackType::Container(element, state, mass) => storage.accepts(
+                        &ItemStackType::Element(element.clone(), state.clone(), *mass),
+                    ),
+                    item_type => storage.accepts(item_type),
+                });
+            if let Some(stack) = stack {
+                // containers are emptied into the pipe as they are pulled
+                let fluid = match &stack.item_type {
+                    ItemStackType::Container(element, state, mass) => {
+                        ItemStackType::Element(element.clone(), state.clone(), *mass)
+                    }
+                    item_type => item_type.clone(),
+                };
+                let filled = storage.fill_with(&fluid, stack.quantity.min(PIPE_IO_RATE));
+                stack.quantity -= filled;
+                if filled > 0 {
+                    transferred.send(ItemTransferred {
+                        from,
+                        to: pipe,
+                        item: fluid.to_item_stack(filled),
+                    });
+                }
+            }
+            output.inventory.items.retain(|item| item.quantity > 0);
+        }
+
+        let input = block_grid
+            .next_in_direction((aabb, trans), block.direction.clone())
+            .and_then(|to| Some((to, input_query.get_mut(to).ok()?)));
+        if let Some((to, mut input)) = input {
+            let Some(fluid) = storage.fluid.clone() else {
+                continue;
+            };
+            if let Some(accepts) = &input.accepts {
+                if !fluid.matches(&accepts.item_type) {
+                    continue;
+                }
+            }
+            if let Some((fluid, taken)) = storage.drain(PIPE_IO_RATE) {
+                let item = fluid.to_item_stack(taken);
+                input.inventory.push(item.clone(), &limits);
+                transferred.send(ItemTransferred {
+                    from: pipe,
+                    to,
+                    item,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn compressor_system(
+    timer: Res<FluidTimer>,
+    mut compressor_query: Query<(
+        Entity,
+        &Aabb,
+        &GlobalTransform,
+        &Block,
+        &Compressor,
+        &mut BlockStatus,
+        Option<&PowerConsumer>,
+    )>,
+    mut storage_query: Query<&mut FluidStorage>,
+    block_grid: Res<BlockGrid>,
+    mut transferred: EventWriter<ItemTransferred>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    for (ent, aabb, trans, block, compressor, mut status, power) in compressor_query.iter_mut() {
+        let intake = block_grid
+            .next_in_direction((aabb, trans), block.direction.reverse())
+            .filter(|intake| storage_query.contains(*intake));
+        let outlet = block_grid
+            .next_in_direction((aabb, trans), block.direction.clone())
+            .filter(|outlet| storage_query.contains(*outlet));
+        let (Some(from), Some(to)) = (intake, outlet) else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+            continue;
+        };
+        let Ok([mut intake, mut outlet]) = storage_query.get_many_mut([from, to]) else {
+            continue;
+        };
+
+        let Some(fluid) = intake.fluid.clone().filter(|fluid| fluid.is_gas()) else {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+            continue;
+        };
+        let room = outlet.max_amount(&fluid).saturating_sub(outlet.amount);
+        if !outlet.accepts(&fluid) || room == 0 {
+            set_status(
+                ent,
+                &mut status,
+                Some(StallReason::OutputFull),
+                &mut stalled,
+            );
+            continue;
+        }
+
+        let speed = power.map_or(1., |power| power.satisfaction);
+        let amount = (compressor.rate as f32 * speed).floor() as u32;
+        if amount == 0 {
+            set_status(ent, &mut status, Some(StallReason::NoPower), &mut stalled);
+            continue;
+        }
+        if let Some((fluid, taken)) = intake.drain(amount.min(room)) {
+            let filled = outlet.fill_with(&fluid, taken);
+            transferred.send(ItemTransferred {
+                from,
+                to,
+                item: fluid.to_item_stack(filled),
+            });
+        }
+        set_status(ent, &mut status, None, &mut stalled);
+    }
+}
+
+fn pipe_network_system(
+    timer: Res<FluidTimer>,
+    mut networks: ResMut<PipeNetworks>,
+    segment_query: Query<(Entity, &Aabb, &GlobalTransform, Option<&Valve>), With<FluidStorage>>,
+    mut storage_query: Query<(&mut FluidStorage, Option<&Temperature>, Option<&Tank>)>,
+    block_grid: Res<BlockGrid>,
+    mut remove: EventWriter<RemoveBlockEvent>,
+) {
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    // flood fill touching segments into networks, closed valves sit in a network of their own
+    let mut segment_network: HashMap<Entity, usize> = HashMap::new();
+    let mut result: Vec<PipeNetwork> = vec![];
+    for (start, _, _, _) in segment_query.iter() {
+        if segment_network.contains_key(&start) {
+            continue;
+        }
+        let network = result.len();
+        result.push(PipeNetwork::default());
+        segment_network.insert(start, network);
+        let mut open = vec![start];
+        while let Some(current) = open.pop() {
+            result[network].segments.push(current);
+            if !is_open(&segment_query, current) {
+                continue;
+            }
+            for next in connected_segments(&segment_query, &block_grid, current) {
+                if !segment_network.contains_key(&next) {
+                    segment_network.insert(next, network);
+                    open.push(next);
+                }
+            }
+        }
+    }
+
+    let mut burst = vec![];
+    for (index, network) in result.iter_mut().enumerate() {
+        // the gas is as hot as the segments holding it, weighted by how much each holds
+        let mut heat = 0.;
+        for segment in network.segments.iter() {
+            let Ok((mut storage, segment_temperature, _)) = storage_query.get_mut(*segment) else {
+                continue;
+            };
+            storage.network = Some(index);
+            network.volume += storage.capacity as f32;
+            if storage.fluid.as_ref().is_some_and(|fluid| fluid.is_gas()) {
+                network.moles += storage.amount as f32;
+                heat += storage.amount as f32
+                    * segment_temperature.map_or(AMBIENT_TEMPERATURE, |t| t.kelvin);
+            }
+        }
+        network.temperature = if network.moles > 0. {
+            heat / network.moles
+        } else {
+            AMBIENT_TEMPERATURE
+        };
+        if network.volume > 0. {
+            network.pressure = network.moles * GAS_CONSTANT * network.temperature / network.volume;
+        }
+
+        for segment in network.segments.iter() {
+            let Ok((_, _, Some(tank))) = storage_query.get(*segment) else {
+                continue;
+            };
+            if network.pressure > tank.pressure_rating {
+                burst.push((*segment, network.pressure));
+            }
+        }
+    }
+
+    // a burst tank spills what it held into the segments around it, the rest escapes
+    for (tank, pressure) in burst.iter() {
+        let Ok((_, aabb, trans, _)) = segment_query.get(*tank) else {
+            continue;
+        };
+        let neighbours = block_grid
+            .neighbours(*tank, (aabb, trans))
+            .into_iter()
+            .filter(|ent| {
+                segment_query.contains(*ent) && !burst.iter().any(|(other, _)| other == ent)
+            })
+            .collect::<Vec<_>>();
+        let spilled = storage_query
+            .get_mut(*tank)
+            .ok()
+            .and_then(|(mut storage, _, _)| {
+                let amount = storage.amount;
+                storage.drain(amount)
+            });
+        if let Some((fluid, mut amount)) = spilled {
+            for (i, neighbour) in neighbours.iter().enumerate() {
+                let share = amount / (neighbours.len() - i) as u32;
+                if let Ok((mut storage, _, _)) = storage_query.get_mut(*neighbour) {
+                    amount -= storage.fill_with(&fluid, share);
+                }
+            }
+            info!(
+                "Tank burst at {:.0}kPa, venting {} {}",
+                pressure, amount, fluid
+            );
+        }
+        // a burst tank is lost, not taken down
+        remove.send(RemoveBlockEvent {
+            entity: *tank,
+            refund: false,
+        });
+    }
+
+    networks.networks = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::Element;
+
+    fn mercury() -> ItemStackType {
+        ItemStackType::Element(Element::Mercury, State::Liquid, None)
+    }
+
+    fn hydrogen() -> ItemStackType {
+        ItemStackType::Element(Element::Hydrogen, State::Gas, None)
+    }
+
+    #[test]
+    fn fill_with_stops_at_capacity() {
+        let mut storage = FluidStorage::new(100);
+
+        assert_eq!(storage.fill_with(&mercury(), 80), 80);
+        assert_eq!(storage.fill_with(&mercury(), 80), 20);
+        assert_eq!(storage.fill(), 1.);
+        assert!(!storage.accepts(&mercury()));
+    }
+
+    #[test]
+    fn gases_compress_past_capacity() {
+        let mut storage = FluidStorage::new(100);
+
+        assert_eq!(storage.max_amount(&hydrogen()), 100 * GAS_COMPRESSION);
+        assert_eq!(storage.fill_with(&hydrogen(), 500), 500);
+        assert_eq!(storage.fill(), 5.);
+    }
+
+    #[test]
+    fn holds_one_fluid_and_no_solids() {
+        let mut storage = FluidStorage::new(100);
+
+        assert!(!storage.accepts(&ItemStackType::Element(Element::Iron, State::Solid, None)));
+        storage.fill_with(&mercury(), 10);
+        assert!(!storage.accepts(&hydrogen()));
+        assert_eq!(storage.fill_with(&hydrogen(), 10), 0);
+    }
+
+    #[test]
+    fn drain_empties_the_fluid() {
+        let mut storage = FluidStorage::new(100);
+        assert_eq!(storage.drain(10), None);
+
+        storage.fill_with(&mercury(), 30);
+        assert_eq!(storage.drain(10), Some((mercury(), 10)));
+        assert_eq!(storage.drain(50), Some((mercury(), 20)));
+        assert_eq!(storage.fluid, None);
+        assert!(storage.accepts(&hydrogen()));
+    }
+}
@@ -1,32 +1,128 @@
-use bevy::{input::mouse::MouseWheel, math::vec3, prelude::*, render::primitives::Aabb};
+use bevy::{
+    input::mouse::MouseWheel,
+    math::vec3,
+    prelude::*,
+    render::primitives::Aabb,
+    utils::{HashMap, HashSet},
+};
 use bevy_mod_picking::{Highlighting, Hover, PickableBundle, PickingRaycastSet};
 use bevy_prototype_debug_lines::DebugLines;
+use enum_iterator::all;
 
 use crate::{
-    blocks::Spawn,
+    blocks::{point_in_direction, Spawn},
     components::{Block, BlockClicked},
-    player::{Modes, Player, SpawnerOptions},
+    events::{BlockPlaced, BlockRemoved},
+    inventory::PlayerInventory,
+    materials::StackLimits,
+    player::{self, GameMode, Modes, Player, SpawnerOptions},
+    research::Research,
 };
 
 pub struct GridPlugin;
 
 impl Plugin for GridPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_build_plane)
+        app.init_resource::<BlockGrid>()
+            .add_startup_system(setup_build_plane)
+            .add_system(update_block_grid.in_base_set(CoreSet::PreUpdate))
             .add_system(grid)
             .add_system(build_plane_manipulation)
             .add_system(grid_cell_select)
-            .add_event::<EmptyGridCellClickedEvent>()
             .add_event::<GridCellHoveredEvent>()
             .add_event::<GridCellClickedEvent>()
+            .add_event::<RemoveBlockEvent>()
             .add_system(grid_cell_hover)
-            .add_system(grid_cell_clicked);
+            .add_system(grid_cell_clicked)
+            .add_system(remove_block.after(grid_cell_clicked));
     }
 }
 
 #[derive(Component)]
 struct BuildPlane {}
 
+/// Which block fills each grid cell, so systems can look up their neighbours instead of
+/// checking every other block.
+#[derive(Resource, Default)]
+pub struct BlockGrid {
+    cells: HashMap<IVec3, Entity>,
+    occupied: HashMap<Entity, Vec<IVec3>>,
+}
+
+impl BlockGrid {
+    pub fn get(&self, cell: IVec3) -> Option<Entity> {
+        self.cells.get(&cell).copied()
+    }
+
+    /// The block filling the cell just past the `direction` face of `a`.
+    pub fn next_in_direction(
+        &self,
+        a: (&Aabb, &GlobalTransform),
+        direction: player::Direction,
+    ) -> Option<Entity> {
+        self.get(point_in_direction(a, direction).floor().as_ivec3())
+    }
+
+    /// Every other block against a face of `entity`.
+    pub fn neighbours(&self, entity: Entity, a: (&Aabb, &GlobalTransform)) -> Vec<Entity> {
+        let mut neighbours = vec![];
+        for direction in all::<player::Direction>() {
+            if let Some(next) = self.next_in_direction(a, direction) {
+                if next != entity && !neighbours.contains(&next) {
+                    neighbours.push(next);
+                }
+            }
+        }
+        neighbours
+    }
+
+    fn insert(&mut self, entity: Entity, aabb: &Aabb, trans: &GlobalTransform) {
+        self.remove(entity);
+        let a = trans.transform_point(aabb.min().into());
+        let b = trans.transform_point(aabb.max().into());
+        // a little slack so a block sitting exactly on a cell edge doesn't claim the next cell
+        let min = (a.min(b) + 0.01).floor().as_ivec3();
+        let max = ((a.max(b) - 0.01).ceil().as_ivec3() - IVec3::ONE).max(min);
+        let mut cells = vec![];
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let cell = IVec3::new(x, y, z);
+                    self.cells.insert(cell, entity);
+                    cells.push(cell);
+                }
+            }
+        }
+        self.occupied.insert(entity, cells);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        for cell in self.occupied.remove(&entity).unwrap_or_default() {
+            if self.cells.get(&cell) == Some(&entity) {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+/// Blocks only get their bounds once spawned, so the grid follows changes to them.
+#[allow(clippy::type_complexity)]
+fn update_block_grid(
+    mut block_grid: ResMut<BlockGrid>,
+    block_query: Query<
+        (Entity, &Aabb, &GlobalTransform),
+        (With<Block>, Or<(Changed<Aabb>, Changed<GlobalTransform>)>),
+    >,
+    mut removed: RemovedComponents<Block>,
+) {
+    for entity in removed.iter() {
+        block_grid.remove(entity);
+    }
+    for (entity, aabb, trans) in block_query.iter() {
+        block_grid.insert(entity, aabb, trans);
+    }
+}
+
 const RENDER_GRID: bool = false;
 const GRID_SIZE: i32 = 1000;
 const GRID_CELL_SIZE: usize = 1;
@@ -111,23 +207,23 @@ fn grid(
     }
 }
 
-pub struct EmptyGridCellClickedEvent {
-    pub grid_cell: Vec3,
-    pub world_pos: Vec3,
-}
-
 pub struct GridCellHoveredEvent {
     pub grid_cell: Vec3,
-    pub world_pos: Vec3,
     pub entity: Option<Entity>,
 }
 
 pub struct GridCellClickedEvent {
     pub grid_cell: Vec3,
-    pub world_pos: Vec3,
     pub entity: Option<Entity>,
 }
 
+/// Takes a block out of the world, whether the player destroyed it or it broke.
+pub struct RemoveBlockEvent {
+    pub entity: Entity,
+    /// Hands the build cost back outside of sandbox, only for blocks the player takes down.
+    pub refund: bool,
+}
+
 #[derive(Default, PartialEq, Clone, Debug)]
 pub enum GridSelectMode {
     #[default]
@@ -135,18 +231,24 @@ pub enum GridSelectMode {
     OnTopOfBlock,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn grid_cell_clicked(
     mut reader: EventReader<GridCellClickedEvent>,
-    player_query: Query<&SpawnerOptions, With<Player>>,
+    mut player_query: Query<(&SpawnerOptions, &mut PlayerInventory), With<Player>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
     current_selected_query: Query<(&Block, Entity), With<BlockClicked>>,
+    game_mode: Res<State<GameMode>>,
+    mut remove: EventWriter<RemoveBlockEvent>,
+    research: Res<Research>,
+    mut placed: EventWriter<BlockPlaced>,
 ) {
-    let Ok(spawner_opts) = player_query.get_single() else {
+    let Ok((spawner_opts, mut player_inventory)) = player_query.get_single_mut() else {
         return;
     };
+    let sandbox = game_mode.0 == GameMode::Sandbox;
 
     for ele in reader.iter() {
         match spawner_opts.player_mode {
@@ -158,17 +260,45 @@ fn grid_cell_clicked(
                     commands.entity(ent).insert(BlockClicked {});
                 }
             }
-            Modes::Build => spawner_opts.block_selection.spawn(
-                &mut commands,
-                &mut meshes,
-                &mut materials,
-                &asset_server,
-                spawner_opts,
-                ele.grid_cell,
-            ),
+            Modes::Build => {
+                if !sandbox && !spawner_opts.block_selection.is_buildable() {
+                    info!(
+                        "{:?} can only be built in sandbox",
+                        spawner_opts.block_selection
+                    );
+                    continue;
+                }
+                if !sandbox && !research.is_block_unlocked(&spawner_opts.block_selection) {
+                    info!("{:?} has not been researched", spawner_opts.block_selection);
+                    continue;
+                }
+                let cost = spawner_opts.block_selection.build_cost();
+                if !sandbox && !player_inventory.pay(cost) {
+                    info!(
+                        "Not enough materials to build {:?}",
+                        spawner_opts.block_selection
+                    );
+                    continue;
+                }
+                let entity = spawner_opts.block_selection.spawn(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &asset_server,
+                    spawner_opts,
+                    ele.grid_cell,
+                );
+                placed.send(BlockPlaced {
+                    entity,
+                    block_type: spawner_opts.block_selection,
+                });
+            }
             Modes::Destroy => {
                 if let Some(ent) = ele.entity {
-                    commands.entity(ent).despawn_recursive();
+                    remove.send(RemoveBlockEvent {
+                        entity: ent,
+                        refund: true,
+                    });
                 }
             }
         }
@@ -176,6 +306,40 @@ fn grid_cell_clicked(
     reader.clear();
 }
 
+fn remove_block(
+    mut reader: EventReader<RemoveBlockEvent>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    mut commands: Commands,
+    block_query: Query<&Block>,
+    game_mode: Res<State<GameMode>>,
+    limits: Res<StackLimits>,
+    mut removed: EventWriter<BlockRemoved>,
+) {
+    let sandbox = game_mode.0 == GameMode::Sandbox;
+    // the same block can be removed twice in a frame, by the player and by breaking
+    let mut seen = HashSet::new();
+    for ele in reader.iter() {
+        if !seen.insert(ele.entity) {
+            continue;
+        }
+        let Some(entity) = commands.get_entity(ele.entity) else {
+            continue;
+        };
+        if let Ok(block) = block_query.get(ele.entity) {
+            if ele.refund && !sandbox {
+                if let Ok(mut player_inventory) = player_query.get_single_mut() {
+                    player_inventory.refund(block.block_type.build_cost(), &limits);
+                }
+            }
+            removed.send(BlockRemoved {
+                entity: ele.entity,
+                block_type: block.block_type,
+            });
+        }
+        entity.despawn_recursive();
+    }
+}
+
 fn grid_cell_hover(
     mut reader: EventReader<GridCellHoveredEvent>,
     mouse: Res<Input<MouseButton>>,
@@ -187,7 +351,6 @@ fn grid_cell_hover(
     for ele in reader.iter() {
         writer.send(GridCellClickedEvent {
             grid_cell: ele.grid_cell,
-            world_pos: ele.world_pos,
             entity: ele.entity,
         });
     }
@@ -218,7 +381,6 @@ fn grid_cell_select(
         if spawner_opts.grid_select_mode == GridSelectMode::Block {
             grid_cell_hovered_event_writer.send(GridCellHoveredEvent {
                 grid_cell: trans.transform_point(aabb.center.into()),
-                world_pos: *position,
                 entity: Some(entity),
             });
         } else {
@@ -228,7 +390,6 @@ fn grid_cell_select(
 
             grid_cell_hovered_event_writer.send(GridCellHoveredEvent {
                 grid_cell: trans.transform_point(aabb.center.into()) - normal,
-                world_pos: *position,
                 entity: Some(entity),
             });
         }
@@ -253,7 +414,6 @@ fn grid_cell_select(
 
     grid_cell_hovered_event_writer.send(GridCellHoveredEvent {
         grid_cell: mod_position.floor() + vec3(0.5, 0.5, 0.5),
-        world_pos: *position,
         entity: None,
     });
 }
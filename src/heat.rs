@@ -0,0 +1,92 @@
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashSet};
+
+use crate::{grid::BlockGrid, time_controls::simulation_running};
+
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Temperature>()
+            .add_system(conduction_system.run_if(simulation_running))
+            .add_system(dissipation_system.run_if(simulation_running));
+    }
+}
+
+/// Room temperature in kelvin, which every block starts at and cools back towards.
+pub const AMBIENT_TEMPERATURE: f32 = 293.15;
+/// Energy per second per kelvin of difference that flows between touching blocks.
+const CONDUCTIVITY: f32 = 5.;
+/// Fraction of the difference from ambient lost to the air each second.
+const DISSIPATION: f32 = 0.01;
+
+#[derive(Component, Reflect)]
+pub struct Temperature {
+    pub kelvin: f32,
+    /// Energy needed to raise the block by one kelvin.
+    pub heat_capacity: f32,
+    /// Past this the block stops working until it cools down.
+    pub max: f32,
+}
+
+impl Default for Temperature {
+    fn default() -> Self {
+        Self::new(1000.)
+    }
+}
+
+impl Temperature {
+    pub fn new(max: f32) -> Self {
+        Self {
+            kelvin: AMBIENT_TEMPERATURE,
+            heat_capacity: 100.,
+            max,
+        }
+    }
+
+    pub fn add_heat(&mut self, energy: f32) {
+        self.kelvin = (self.kelvin + energy / self.heat_capacity).max(0.);
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.kelvin > self.max
+    }
+}
+
+fn conduction_system(
+    block_query: Query<(Entity, &Aabb, &GlobalTransform), With<Temperature>>,
+    mut temperature_query: Query<&mut Temperature>,
+    block_grid: Res<BlockGrid>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    let mut pairs = HashSet::new();
+    for (a, aabb, trans) in block_query.iter() {
+        for b in block_grid.neighbours(a, (aabb, trans)) {
+            pairs.insert((a.min(b), a.max(b)));
+        }
+    }
+
+    for (a, b) in pairs {
+        let Ok([mut a, mut b]) = temperature_query.get_many_mut([a, b]) else {
+            continue;
+        };
+
+        // never move more than it takes for both sides to meet in the middle
+        let equalise = (a.kelvin - b.kelvin) * a.heat_capacity * b.heat_capacity
+            / (a.heat_capacity + b.heat_capacity);
+        let mut flow = CONDUCTIVITY * (a.kelvin - b.kelvin) * delta;
+        if flow.abs() > equalise.abs() {
+            flow = equalise;
+        }
+
+        a.add_heat(-flow);
+        b.add_heat(flow);
+    }
+}
+
+fn dissipation_system(mut query: Query<&mut Temperature>, time: Res<Time>) {
+    let loss = (DISSIPATION * time.delta_seconds()).min(1.);
+    for mut temperature in query.iter_mut() {
+        temperature.kelvin += (AMBIENT_TEMPERATURE - temperature.kelvin) * loss;
+    }
+}
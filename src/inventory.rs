@@ -1 +1,223 @@
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+use lazy_static::lazy_static;
 
+use crate::{
+    blocks::BlockType,
+    materials::{Element, Inventory, ItemStack, StackLimits, State},
+};
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PlayerInventory>();
+    }
+}
+
+/// What the player is carrying, which pays for and is refunded by building.
+#[derive(Component, Default, Reflect)]
+pub struct PlayerInventory {
+    pub inventory: Inventory,
+}
+
+fn solid(element: Element, quantity: u32) -> ItemStack {
+    element.to_item_stack(State::Solid, quantity)
+}
+
+lazy_static! {
+    /// What a survival game starts with, enough for a first extractor and a few belts to get
+    /// materials moving.
+    pub static ref STARTING_KIT: Vec<ItemStack> =
+        vec![solid(Element::Iron, 20), solid(Element::Copper, 10)];
+    pub static ref BUILD_COSTS: HashMap<BlockType, Vec<ItemStack>> = HashMap::from([
+        (
+            BlockType::Furnace,
+            vec![solid(Element::Iron, 8), solid(Element::Silicon, 4)]
+        ),
+        (BlockType::Conveyor, vec![solid(Element::Iron, 1)]),
+        (
+            BlockType::Splitter,
+            vec![solid(Element::Iron, 2), solid(Element::Copper, 1)]
+        ),
+        (BlockType::Storage, vec![solid(Element::Iron, 4)]),
+        (
+            BlockType::Grabber,
+            vec![solid(Element::Iron, 2), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Generator,
+            vec![solid(Element::Iron, 5), solid(Element::Copper, 5)]
+        ),
+        (
+            BlockType::PowerPole,
+            vec![solid(Element::Copper, 2), solid(Element::Aluminium, 1)]
+        ),
+        (
+            BlockType::Turbine,
+            vec![solid(Element::Iron, 6), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Heater,
+            vec![solid(Element::Iron, 4), solid(Element::Copper, 4)]
+        ),
+        (
+            BlockType::Photovoltaic,
+            vec![
+                solid(Element::Silicon, 4),
+                solid(Element::Copper, 2),
+                solid(Element::Aluminium, 2)
+            ]
+        ),
+        (
+            BlockType::Motor,
+            vec![solid(Element::Iron, 4), solid(Element::Copper, 4)]
+        ),
+        (
+            BlockType::Accumulator,
+            vec![
+                solid(Element::Lithium, 4),
+                solid(Element::Copper, 2),
+                solid(Element::Iron, 2)
+            ]
+        ),
+        (
+            BlockType::Centrifuge,
+            vec![solid(Element::Iron, 8), solid(Element::Nickel, 2)]
+        ),
+        (BlockType::Pipe, vec![solid(Element::Iron, 1)]),
+        (BlockType::Tank, vec![solid(Element::Iron, 6)]),
+        (
+            BlockType::Melter,
+            vec![solid(Element::Iron, 6), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Boiler,
+            vec![solid(Element::Iron, 6), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Condenser,
+            vec![solid(Element::Iron, 4), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Freezer,
+            vec![
+                solid(Element::Iron, 4),
+                solid(Element::Copper, 2),
+                solid(Element::Aluminium, 2)
+            ]
+        ),
+        (
+            BlockType::PlasmaTorch,
+            vec![solid(Element::Tungsten, 2), solid(Element::Copper, 4)]
+        ),
+        (
+            BlockType::Compressor,
+            vec![solid(Element::Iron, 4), solid(Element::Copper, 2)]
+        ),
+        (BlockType::Valve, vec![solid(Element::Iron, 2)]),
+        (
+            BlockType::Extractor,
+            vec![solid(Element::Iron, 6), solid(Element::Copper, 2)]
+        ),
+        (
+            BlockType::Lab,
+            vec![solid(Element::Iron, 6), solid(Element::Copper, 4)]
+        ),
+    ]);
+}
+
+impl BlockType {
+    /// Materials taken from the player to place this block, and given back when it is destroyed.
+    pub fn build_cost(&self) -> &'static [ItemStack] {
+        BUILD_COSTS.get(self).map_or(&[], |cost| cost.as_slice())
+    }
+
+    /// Whether the block can be built outside of sandbox, debug blocks have no cost and can't be.
+    pub fn is_buildable(&self) -> bool {
+        BUILD_COSTS.contains_key(self)
+    }
+}
+
+impl PlayerInventory {
+    pub fn can_afford(&self, cost: &[ItemStack]) -> bool {
+        cost.iter().all(|item| self.inventory.contains(item))
+    }
+
+    /// Takes `cost` out of the inventory, or nothing at all if any of it is missing.
+    pub fn pay(&mut self, cost: &[ItemStack]) -> bool {
+        if !self.can_afford(cost) {
+            return false;
+        }
+        for item in cost {
+            self.inventory.remove(item);
+        }
+        true
+    }
+
+    pub fn refund(&mut self, cost: &[ItemStack], limits: &StackLimits) {
+        for item in cost {
+            self.inventory.push(item.clone(), limits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(items: Vec<ItemStack>) -> PlayerInventory {
+        PlayerInventory {
+            inventory: items.into(),
+        }
+    }
+
+    #[test]
+    fn pay_takes_the_whole_cost() {
+        let mut player = player(vec![solid(Element::Iron, 10), solid(Element::Copper, 5)]);
+
+        assert!(player.pay(&[solid(Element::Iron, 4), solid(Element::Copper, 5)]));
+        assert_eq!(
+            player
+                .inventory
+                .quantity(&solid(Element::Iron, 0).item_type),
+            6
+        );
+        assert_eq!(
+            player
+                .inventory
+                .quantity(&solid(Element::Copper, 0).item_type),
+            0
+        );
+    }
+
+    #[test]
+    fn pay_takes_nothing_when_short() {
+        let mut player = player(vec![solid(Element::Iron, 10), solid(Element::Copper, 2)]);
+
+        assert!(!player.pay(&[solid(Element::Iron, 4), solid(Element::Copper, 5)]));
+        assert_eq!(
+            player
+                .inventory
+                .quantity(&solid(Element::Iron, 0).item_type),
+            10
+        );
+        assert_eq!(
+            player
+                .inventory
+                .quantity(&solid(Element::Copper, 0).item_type),
+            2
+        );
+    }
+
+    #[test]
+    fn refund_returns_the_build_cost() {
+        let mut player = player(STARTING_KIT.clone());
+        let cost = &BUILD_COSTS[&BlockType::Extractor];
+
+        assert!(player.pay(cost));
+        player.refund(cost, &StackLimits::default());
+        for item in STARTING_KIT.iter() {
+            assert_eq!(player.inventory.quantity(&item.item_type), item.quantity);
+        }
+    }
+}
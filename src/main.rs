@@ -1,11 +1,27 @@
+mod alerts;
+mod analysis;
 mod blocks;
+mod calculator;
 mod city_planner;
 mod components;
+mod config;
+mod encyclopedia;
+mod events;
+mod fluids;
 mod grid;
+mod heat;
 mod inventory;
 mod materials;
+mod overlays;
 mod player;
+mod power;
+mod quests;
+mod radiation;
 mod reactions;
+mod research;
+mod statistics;
+mod time_controls;
+mod ui;
 
 use std::f32::consts::PI;
 
@@ -13,18 +29,38 @@ use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_mod_picking::{DebugCursorPickingPlugin, DefaultPickingPlugins};
 
+use alerts::AlertsPlugin;
+use analysis::AnalysisPlugin;
 use bevy_obj::ObjPlugin;
 use bevy_prototype_debug_lines::DebugLinesPlugin;
 use bevy_rapier3d::prelude::*;
 use blocks::BlockPlugin;
+use calculator::CalculatorPlugin;
 use city_planner::CityPlannerPlugin;
 use components::ComponentPlugin;
+use encyclopedia::EncyclopediaPlugin;
+use events::EventsPlugin;
+use fluids::FluidsPlugin;
 use grid::GridPlugin;
+use heat::HeatPlugin;
+use inventory::InventoryPlugin;
+use overlays::OverlaysPlugin;
 use player::PlayerPlugin;
+use power::PowerPlugin;
+use quests::QuestsPlugin;
+use radiation::RadiationPlugin;
+use reactions::ReactionsPlugin;
+use research::ResearchPlugin;
+use statistics::StatisticsPlugin;
+use time_controls::TimeControlsPlugin;
+use ui::UiPlugin;
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            watch_for_changes: true,
+            ..default()
+        }))
         .add_plugin(ObjPlugin)
         .add_plugin(WorldInspectorPlugin::new())
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
@@ -38,6 +74,23 @@ fn main() {
         .add_plugin(DebugCursorPickingPlugin)
         .add_plugin(BlockPlugin)
         .add_plugin(materials::MaterialsPlugin)
+        .add_plugin(PowerPlugin)
+        .add_plugin(RadiationPlugin)
+        .add_plugin(FluidsPlugin)
+        .add_plugin(HeatPlugin)
+        .add_plugin(ReactionsPlugin)
+        .add_plugin(UiPlugin)
+        .add_plugin(EncyclopediaPlugin)
+        .add_plugin(CalculatorPlugin)
+        .add_plugin(InventoryPlugin)
+        .add_plugin(ResearchPlugin)
+        .add_plugin(EventsPlugin)
+        .add_plugin(QuestsPlugin)
+        .add_plugin(StatisticsPlugin)
+        .add_plugin(AnalysisPlugin)
+        .add_plugin(OverlaysPlugin)
+        .add_plugin(AlertsPlugin)
+        .add_plugin(TimeControlsPlugin)
         .add_startup_system(setup_lights)
         .run();
 }
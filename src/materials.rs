@@ -1,8 +1,11 @@
-use std::{fmt::Display, fmt::Formatter, time::Duration};
+use std::{collections::HashMap as StdHashMap, fmt::Display, fmt::Formatter, time::Duration};
 
-use bevy::{prelude::*, utils::hashbrown::HashMap};
-use enum_iterator::Sequence;
+use bevy::{prelude::*, reflect::TypeUuid, utils::hashbrown::HashMap};
+use enum_iterator::{all, Sequence};
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RonAsset, RonAssetPlugin};
 
 pub struct MaterialsPlugin;
 
@@ -15,11 +18,17 @@ impl Plugin for MaterialsPlugin {
         app.register_type::<ItemStackType>();
         app.register_type::<Energy>();
         app.register_type::<Inventory>();
+        app.init_resource::<StackLimits>()
+            .add_plugin(RonAssetPlugin::<StackLimits>::new(
+                STACK_LIMITS_PATH,
+                "limits.ron",
+            ));
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Reflect, FromReflect, Default)]
 pub struct Reaction {
+    pub name: String,
     pub input: Vec<ItemStack>,
     pub output: Vec<ItemStack>,
     pub duration: Duration,
@@ -27,6 +36,9 @@ pub struct Reaction {
 
 impl Display for Reaction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.name.is_empty() {
+            write!(f, "{}: ", self.name)?;
+        }
         for item in &self.input {
             write!(f, "{}", item)?;
         }
@@ -43,10 +55,15 @@ impl Reaction {
         if input.is_empty() {
             return false;
         }
-        self.input.iter().all(|item| input.contains(item))
+        self.input.iter().all(|item| input.contains_matching(item))
     }
 
-    pub fn run(&self, input_inventory: &mut Inventory, output_inventory: &mut Inventory) {
+    pub fn run(
+        &self,
+        input_inventory: &mut Inventory,
+        output_inventory: &mut Inventory,
+        limits: &StackLimits,
+    ) {
         if input_inventory.is_empty() {
             return;
         }
@@ -56,16 +73,16 @@ impl Reaction {
         }
 
         self.input.iter().for_each(|ele| {
-            input_inventory.remove(ele);
+            input_inventory.remove_matching(ele);
         });
 
         self.output.iter().for_each(|ele| {
-            output_inventory.push(ele.clone());
+            output_inventory.push(ele.clone(), limits);
         });
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Reflect, FromReflect)]
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
 pub struct ItemStack {
     pub item_type: ItemStackType,
     pub quantity: u32,
@@ -77,30 +94,66 @@ impl Display for ItemStack {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect, FromReflect)]
+/// Elements carry an optional isotope mass number. `None` is the natural mix, and as a
+/// reaction input it matches any isotope of the element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect, FromReflect, Serialize, Deserialize)]
 pub enum ItemStackType {
-    Element(Element, State),
+    Element(Element, State, Option<u16>),
     Energy(Energy),
+    /// A sealed unit of a fluid element, so it can ride conveyors and grabbers.
+    Container(Element, State, Option<u16>),
+    Compound(Compound, State),
 }
 
 impl Display for ItemStackType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            ItemStackType::Element(element, state, None) => {
+                write!(f, "Element({}, {:?})", element, state)
+            }
+            ItemStackType::Element(element, state, Some(mass)) => {
+                write!(f, "Element({}-{}, {:?})", element, mass, state)
+            }
+            ItemStackType::Energy(energy) => write!(f, "Energy({:?})", energy),
+            ItemStackType::Container(element, state, mass) => write!(
+                f,
+                "Container({})",
+                ItemStackType::Element(element.clone(), state.clone(), *mass)
+            ),
+            ItemStackType::Compound(compound, state) => {
+                write!(f, "Compound({}, {:?})", compound, state)
+            }
+        }
     }
 }
 
 impl ItemStackType {
-    pub fn to_item_stack(self, quantity: u32) -> ItemStack {
+    pub fn to_item_stack(&self, quantity: u32) -> ItemStack {
         ItemStack {
-            item_type: self,
+            item_type: self.clone(),
             quantity,
         }
     }
 
-    pub fn quantity_limit(&self) -> u32 {
-        *ITEMSTACKTYPE_QUANTITY_LIMITS
-            .get(self)
-            .unwrap_or(&DEFAULT_STATIC_LIMIT)
+    /// The element in this item, whether loose or in a container.
+    pub fn element(&self) -> Option<&Element> {
+        match self {
+            ItemStackType::Element(element, _, _) | ItemStackType::Container(element, _, _) => {
+                Some(element)
+            }
+            ItemStackType::Energy(_) | ItemStackType::Compound(_, _) => None,
+        }
+    }
+
+    /// Whether this item satisfies `pattern`, where a pattern without an isotope accepts any.
+    pub fn matches(&self, pattern: &ItemStackType) -> bool {
+        match (self, pattern) {
+            (
+                ItemStackType::Element(element, state, _),
+                ItemStackType::Element(pattern_element, pattern_state, None),
+            ) => element == pattern_element && state == pattern_state,
+            _ => self == pattern,
+        }
     }
 }
 
@@ -117,21 +170,65 @@ impl From<Vec<ItemStack>> for Inventory {
 
 impl Inventory {
     pub fn contains(&self, filter: &ItemStack) -> bool {
-        let total_local_quantity = self
-            .items
+        self.quantity(&filter.item_type) >= filter.quantity
+    }
+
+    /// Whether all of `item` fits without going over its stack limit.
+    pub fn has_room(&self, item: &ItemStack, limits: &StackLimits) -> bool {
+        self.quantity(&item.item_type) + item.quantity <= limits.limit(&item.item_type)
+    }
+
+    pub fn contains_matching(&self, filter: &ItemStack) -> bool {
+        self.quantity_matching(&filter.item_type) >= filter.quantity
+    }
+
+    pub fn quantity_matching(&self, pattern: &ItemStackType) -> u32 {
+        self.items
+            .iter()
+            .filter(|item| item.item_type.matches(pattern))
+            .map(|item| item.quantity)
+            .sum::<u32>()
+    }
+
+    /// Removes `item.quantity` of anything matching `item.item_type`, see [`ItemStackType::matches`].
+    pub fn remove_matching(&mut self, item: &ItemStack) {
+        let mut amount_left_to_take: u32 = item.quantity;
+
+        for stack in self.items.iter_mut() {
+            if amount_left_to_take == 0 {
+                break;
+            }
+            if !stack.item_type.matches(&item.item_type) || stack.quantity == 0 {
+                continue;
+            }
+            let taken = stack.quantity.min(amount_left_to_take);
+            stack.quantity -= taken;
+            amount_left_to_take -= taken;
+        }
+
+        self.items.retain(|item| item.quantity > 0);
+    }
+
+    pub fn quantity(&self, item_type: &ItemStackType) -> u32 {
+        self.items
             .iter()
             .filter_map(|item| {
-                if item.item_type == filter.item_type {
+                if item.item_type == *item_type {
                     Some(item.quantity)
                 } else {
                     None
                 }
             })
-            .sum::<u32>();
-
-        total_local_quantity >= filter.quantity
+            .sum::<u32>()
     }
-    pub fn transfer(&mut self, requested: &ItemStack, destination: &mut Inventory) {
+
+    /// Moves `requested` into `destination` if all of it is here, returning what was moved.
+    pub fn transfer(
+        &mut self,
+        requested: &ItemStack,
+        destination: &mut Inventory,
+        limits: &StackLimits,
+    ) -> Option<ItemStack> {
         let total_local_quantity = self
             .items
             .iter()
@@ -145,7 +242,7 @@ impl Inventory {
             .sum::<u32>();
 
         if total_local_quantity < requested.quantity {
-            return;
+            return None;
         }
 
         let mut amount_left_to_take: u32 = requested.quantity;
@@ -159,31 +256,43 @@ impl Inventory {
             }
             if item.quantity > amount_left_to_take {
                 item.quantity -= amount_left_to_take;
-                destination.push(ItemStack {
-                    item_type: item.item_type.clone(),
-                    quantity: amount_left_to_take,
-                });
+                destination.push(
+                    ItemStack {
+                        item_type: item.item_type.clone(),
+                        quantity: amount_left_to_take,
+                    },
+                    limits,
+                );
                 amount_left_to_take = 0;
             } else {
-                destination.push(item.clone());
+                destination.push(item.clone(), limits);
                 amount_left_to_take -= item.quantity;
                 item.quantity = 0;
             }
         }
 
         self.items.retain(|item| item.quantity > 0);
+        Some(requested.clone())
     }
 
-    pub fn transfer_first(&mut self, destination: &mut Inventory) {
-        if self.items.is_empty() {
-            return;
-        }
-        let item = self.items.remove(0);
-        destination.push(item);
+    pub fn transfer_first_where<F>(
+        &mut self,
+        destination: &mut Inventory,
+        limits: &StackLimits,
+        predicate: F,
+    ) -> Option<ItemStack>
+    where
+        F: Fn(&ItemStack) -> bool,
+    {
+        let index = self.items.iter().position(predicate)?;
+        let item = self.items.remove(index);
+        destination.push(item.clone(), limits);
+        Some(item)
     }
 
-    pub fn push(&mut self, item: ItemStack) {
+    pub fn push(&mut self, item: ItemStack, limits: &StackLimits) {
         let mut amount_left_to_add: u32 = item.quantity;
+        let limit = limits.limit(&item.item_type);
 
         for stack in self.items.iter_mut() {
             if amount_left_to_add == 0 {
@@ -193,15 +302,15 @@ impl Inventory {
                 continue;
             }
 
-            if stack.quantity + amount_left_to_add < stack.item_type.quantity_limit() {
+            if stack.quantity + amount_left_to_add < limit {
                 stack.quantity += amount_left_to_add;
                 amount_left_to_add = 0;
-            } else if stack.quantity + amount_left_to_add > stack.item_type.quantity_limit() {
-                amount_left_to_add -= stack.item_type.quantity_limit() - stack.quantity;
-                stack.quantity = stack.item_type.quantity_limit();
+            } else if stack.quantity + amount_left_to_add > limit {
+                amount_left_to_add -= limit - stack.quantity;
+                stack.quantity = limit;
             } else {
                 amount_left_to_add = 0;
-                stack.quantity = stack.item_type.quantity_limit();
+                stack.quantity = limit;
             }
         }
 
@@ -210,7 +319,7 @@ impl Inventory {
         }
 
         while amount_left_to_add > 0 {
-            if amount_left_to_add < item.item_type.quantity_limit() {
+            if amount_left_to_add < limit {
                 self.items.push(ItemStack {
                     item_type: item.item_type.clone(),
                     quantity: amount_left_to_add,
@@ -219,9 +328,9 @@ impl Inventory {
             }
             self.items.push(ItemStack {
                 item_type: item.item_type.clone(),
-                quantity: item.item_type.quantity_limit(),
+                quantity: limit,
             });
-            amount_left_to_add -= item.item_type.quantity_limit();
+            amount_left_to_add -= limit;
         }
     }
 
@@ -257,13 +366,108 @@ impl Inventory {
     }
 }
 
+/// Where the stack limits are loaded from, reloading it while the game runs applies the changes.
+const STACK_LIMITS_PATH: &str = "config/default.limits.ron";
+
+/// How many of an item fit in one stack. An item's own entry wins over the default for its
+/// state, which wins over `default`.
+#[derive(Resource, Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "5f0b7d0e-3c1a-4d8e-9a57-2b6f4c1e8d21"]
+pub struct StackLimits {
+    pub default: u32,
+    #[serde(default)]
+    pub states: StdHashMap<State, u32>,
+    #[serde(default)]
+    pub items: StdHashMap<ItemStackType, u32>,
+}
+
+impl Default for StackLimits {
+    fn default() -> Self {
+        Self {
+            default: 64,
+            states: StdHashMap::from([
+                (State::Solid, 64),
+                (State::Liquid, 100),
+                (State::Gas, 500),
+                (State::Plasma, 100),
+            ]),
+            items: StdHashMap::from([(
+                ItemStackType::Element(Element::Hydrogen, State::Solid, None),
+                100,
+            )]),
+        }
+    }
+}
+
+impl StackLimits {
+    pub fn limit(&self, item_type: &ItemStackType) -> u32 {
+        if let Some(limit) = self.items.get(item_type) {
+            return *limit;
+        }
+        match item_type {
+            ItemStackType::Element(_, state, _)
+            | ItemStackType::Container(_, state, _)
+            | ItemStackType::Compound(_, state) => *self.states.get(state).unwrap_or(&self.default),
+            ItemStackType::Energy(_) => self.default,
+        }
+    }
+}
+
+impl RonAsset for StackLimits {
+    type Target = StackLimits;
+
+    fn apply(&self, target: &mut StackLimits) {
+        *target = self.clone();
+    }
+}
+
+// mass number and abundance of each isotope in the natural mix, lightest first
 lazy_static! {
-    pub static ref ITEMSTACKTYPE_QUANTITY_LIMITS: HashMap<ItemStackType, u32> =
-        HashMap::from([(ItemStackType::Element(Element::Hydrogen, State::Solid), 100)]);
-    pub static ref DEFAULT_STATIC_LIMIT: u32 = 64;
+    pub static ref NATURAL_ISOTOPES: HashMap<Element, Vec<(u16, f32)>> = HashMap::from([
+        (Element::Hydrogen, vec![(1, 0.99985), (2, 0.00015)]),
+        (Element::Lithium, vec![(6, 0.0759), (7, 0.9241)]),
+        (Element::Boron, vec![(10, 0.199), (11, 0.801)]),
+        (Element::Carbon, vec![(12, 0.9893), (13, 0.0107)]),
+        (Element::Nitrogen, vec![(14, 0.99636), (15, 0.00364)]),
+        (
+            Element::Oxygen,
+            vec![(16, 0.99757), (17, 0.00038), (18, 0.00205)]
+        ),
+        (
+            Element::Silicon,
+            vec![(28, 0.92223), (29, 0.04685), (30, 0.03092)]
+        ),
+        (Element::Chlorine, vec![(35, 0.7576), (37, 0.2424)]),
+        (
+            Element::Potassium,
+            vec![(39, 0.93258), (40, 0.00012), (41, 0.0673)]
+        ),
+        (
+            Element::Iron,
+            vec![(54, 0.05845), (56, 0.91754), (57, 0.02119), (58, 0.00282)]
+        ),
+        (Element::Copper, vec![(63, 0.6915), (65, 0.3085)]),
+        (Element::Thorium, vec![(232, 1.0)]),
+        (
+            Element::Uranium,
+            vec![(234, 0.000054), (235, 0.007204), (238, 0.992742)]
+        ),
+    ]);
 }
 
-#[derive(Clone, Debug, PartialEq, Reflect, Eq, Hash, FromReflect, Sequence, Default)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+    Eq,
+    Hash,
+    FromReflect,
+    Sequence,
+    Default,
+    Serialize,
+    Deserialize,
+)]
 pub enum Energy {
     #[default]
     Mechanical,
@@ -282,9 +486,9 @@ pub enum Energy {
 }
 
 impl Energy {
-    pub fn to_item_stack(self, quantity: u32) -> ItemStack {
+    pub fn to_item_stack(&self, quantity: u32) -> ItemStack {
         ItemStack {
-            item_type: ItemStackType::Energy(self),
+            item_type: ItemStackType::Energy(self.clone()),
             quantity,
         }
     }
@@ -296,6 +500,40 @@ impl Display for Energy {
     }
 }
 
+/// Substances made of more than one element.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+    Eq,
+    Hash,
+    FromReflect,
+    Sequence,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum Compound {
+    #[default]
+    Water,
+}
+
+impl Compound {
+    pub fn to_item_stack(&self, state: State, quantity: u32) -> ItemStack {
+        ItemStack {
+            item_type: ItemStackType::Compound(self.clone(), state),
+            quantity,
+        }
+    }
+}
+
+impl Display for Compound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 // pub enum IonizingRadiation {
 //     Ultraviolet,
 //     Xray,
@@ -314,7 +552,19 @@ impl Display for Energy {
 //     Blackbody,
 // }
 
-#[derive(Clone, Debug, PartialEq, Reflect, Eq, Hash, FromReflect, Sequence, Default)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+    Eq,
+    Hash,
+    FromReflect,
+    Sequence,
+    Default,
+    Serialize,
+    Deserialize,
+)]
 pub enum State {
     #[default]
     Solid,
@@ -329,16 +579,21 @@ impl Display for State {
     }
 }
 
-impl State {
-    pub fn to_item_stack(self, element: Element, quantity: u32) -> ItemStack {
-        ItemStack {
-            item_type: ItemStackType::Element(element, self),
-            quantity,
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Reflect, Eq, Hash, FromReflect, Sequence, Default)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+    Eq,
+    Hash,
+    FromReflect,
+    Sequence,
+    Default,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
 pub enum Element {
     #[default]
     Hydrogen,
@@ -462,16 +717,450 @@ pub enum Element {
 }
 
 impl Element {
-    pub fn to_item_stack(self, state: State, quantity: u32) -> ItemStack {
+    pub fn to_item_stack(&self, state: State, quantity: u32) -> ItemStack {
         ItemStack {
-            item_type: ItemStackType::Element(self, state),
+            item_type: ItemStackType::Element(self.clone(), state, None),
             quantity,
         }
     }
+
+    pub fn natural_isotopes(&self) -> Option<&'static Vec<(u16, f32)>> {
+        NATURAL_ISOTOPES.get(self)
+    }
+
+    /// Melting and boiling points in kelvin at standard pressure. `None` for the superheavy
+    /// elements that have never been made in bulk. Arsenic sublimes, so it boils below its
+    /// melting point.
+    pub fn phase_points(&self) -> Option<(f32, f32)> {
+        Some(match self {
+            Element::Hydrogen => (14.01, 20.28),
+            Element::Helium => (0.95, 4.22),
+            Element::Lithium => (453.69, 1615.),
+            Element::Beryllium => (1560., 2742.),
+            Element::Boron => (2349., 4200.),
+            Element::Carbon => (3800., 4300.),
+            Element::Nitrogen => (63.15, 77.36),
+            Element::Oxygen => (54.36, 90.2),
+            Element::Fluorine => (53.53, 85.03),
+            Element::Neon => (24.56, 27.07),
+            Element::Sodium => (370.87, 1156.),
+            Element::Magnesium => (923., 1363.),
+            Element::Aluminium => (933.47, 2792.),
+            Element::Silicon => (1687., 3538.),
+            Element::Phosphorus => (317.3, 550.),
+            Element::Sulfur => (388.36, 717.87),
+            Element::Chlorine => (171.6, 239.11),
+            Element::Argon => (83.8, 87.3),
+            Element::Potassium => (336.53, 1032.),
+            Element::Calcium => (1115., 1757.),
+            Element::Scandium => (1814., 3109.),
+            Element::Titanium => (1941., 3560.),
+            Element::Vanadium => (2183., 3680.),
+            Element::Chromium => (2180., 2944.),
+            Element::Manganese => (1519., 2334.),
+            Element::Iron => (1811., 3134.),
+            Element::Cobalt => (1768., 3200.),
+            Element::Nickel => (1728., 3186.),
+            Element::Copper => (1357.77, 2835.),
+            Element::Zinc => (692.68, 1180.),
+            Element::Gallium => (302.91, 2477.),
+            Element::Germanium => (1211.4, 3106.),
+            Element::Arsenic => (1090., 887.),
+            Element::Selenium => (494., 958.),
+            Element::Bromine => (265.8, 332.),
+            Element::Krypton => (115.79, 119.93),
+            Element::Rubidium => (312.46, 961.),
+            Element::Strontium => (1050., 1655.),
+            Element::Yttrium => (1799., 3609.),
+            Element::Zirconium => (2128., 4682.),
+            Element::Niobium => (2750., 5017.),
+            Element::Molybdenum => (2896., 4912.),
+            Element::Technetium => (2430., 4538.),
+            Element::Ruthenium => (2607., 4423.),
+            Element::Rhodium => (2237., 3968.),
+            Element::Palladium => (1828.05, 3236.),
+            Element::Silver => (1234.93, 2435.),
+            Element::Cadmium => (594.22, 1040.),
+            Element::Indium => (429.75, 2345.),
+            Element::Tin => (505.08, 2875.),
+            Element::Antimony => (903.78, 1860.),
+            Element::Tellurium => (722.66, 1261.),
+            Element::Iodine => (386.85, 457.4),
+            Element::Xenon => (161.4, 165.03),
+            Element::Cesium => (301.59, 944.),
+            Element::Barium => (1000., 2170.),
+            Element::Lanthanum => (1193., 3737.),
+            Element::Cerium => (1068., 3716.),
+            Element::Praseodymium => (1208., 3793.),
+            Element::Neodymium => (1297., 3347.),
+            Element::Promethium => (1315., 3273.),
+            Element::Samarium => (1345., 2067.),
+            Element::Europium => (1099., 1802.),
+            Element::Gadolinium => (1585., 3546.),
+            Element::Terbium => (1629., 3503.),
+            Element::Dysprosium => (1680., 2840.),
+            Element::Holmium => (1734., 2993.),
+            Element::Erbium => (1802., 3141.),
+            Element::Thulium => (1818., 2223.),
+            Element::Ytterbium => (1097., 1469.),
+            Element::Lutetium => (1925., 3675.),
+            Element::Hafnium => (2506., 4876.),
+            Element::Tantalum => (3290., 5731.),
+            Element::Tungsten => (3695., 6203.),
+            Element::Rhenium => (3459., 5869.),
+            Element::Osmium => (3306., 5285.),
+            Element::Iridium => (2719., 4701.),
+            Element::Platinum => (2041.4, 4098.),
+            Element::Gold => (1337.33, 3129.),
+            Element::Mercury => (234.43, 629.88),
+            Element::Thallium => (577., 1746.),
+            Element::Lead => (600.61, 2022.),
+            Element::Bismuth => (544.7, 1837.),
+            Element::Polonium => (527., 1235.),
+            Element::Astatine => (575., 610.),
+            Element::Radon => (202., 211.3),
+            Element::Francium => (300., 950.),
+            Element::Radium => (973., 2010.),
+            Element::Actinium => (1323., 3471.),
+            Element::Thorium => (2023., 5061.),
+            Element::Protactinium => (1841., 4300.),
+            Element::Uranium => (1405.3, 4404.),
+            Element::Neptunium => (917., 4273.),
+            Element::Plutonium => (912.5, 3501.),
+            Element::Americium => (1449., 2880.),
+            Element::Curium => (1613., 3383.),
+            Element::Berkelium => (1259., 2900.),
+            Element::Californium => (1173., 1743.),
+            Element::Einsteinium => (1133., 1269.),
+            _ => return None,
+        })
+    }
+
+    /// Units of energy to move one unit of this element between two neighbouring states.
+    pub fn phase_change_energy(&self, from: &State, to: &State) -> Option<u32> {
+        let (melting, boiling) = self.phase_points()?;
+        let energy = match (from, to) {
+            (State::Solid, State::Liquid) | (State::Liquid, State::Solid) => melting / 100.,
+            (State::Liquid, State::Gas) | (State::Gas, State::Liquid) => boiling / 100.,
+            (State::Gas, State::Plasma) | (State::Plasma, State::Gas) => 25. + boiling / 50.,
+            _ => return None,
+        };
+        Some((energy.ceil() as u32).max(1))
+    }
+
+    /// Follows the order of the enum, so Hydrogen is 1.
+    pub fn atomic_number(&self) -> u8 {
+        self.clone() as u8 + 1
+    }
+
+    pub fn period(&self) -> u8 {
+        PERIOD_ENDS
+            .iter()
+            .position(|end| self.atomic_number() <= *end)
+            .unwrap_or(PERIOD_ENDS.len() - 1) as u8
+            + 1
+    }
+
+    /// Column in the 18 column table. `None` for the lanthanides and actinides other than
+    /// Lutetium and Lawrencium, which sit in group 3.
+    pub fn group(&self) -> Option<u8> {
+        let period = self.period() as usize;
+        let start = if period > 1 {
+            PERIOD_ENDS[period - 2]
+        } else {
+            0
+        };
+        let offset = self.atomic_number() - start;
+        match period {
+            1 if offset == 1 => Some(1),
+            1 => Some(18),
+            2 | 3 if offset <= 2 => Some(offset),
+            2 | 3 => Some(offset + 10),
+            4 | 5 => Some(offset),
+            _ if offset <= 2 => Some(offset),
+            _ if offset <= 16 => None,
+            _ => Some(offset - 14),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        SYMBOLS[self.atomic_number() as usize - 1]
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        all::<Element>().find(|element| element.symbol() == symbol)
+    }
+
+    pub fn block(&self) -> ElementBlock {
+        match (self.group(), self) {
+            (None, _) => ElementBlock::F,
+            (_, Element::Helium) => ElementBlock::S,
+            (Some(1..=2), _) => ElementBlock::S,
+            (Some(3..=12), _) => ElementBlock::D,
+            _ => ElementBlock::P,
+        }
+    }
+
+    pub fn category(&self) -> ElementCategory {
+        let number = self.atomic_number();
+        match (self.group(), self) {
+            _ if (57..=71).contains(&number) => ElementCategory::Lanthanide,
+            _ if (89..=103).contains(&number) => ElementCategory::Actinide,
+            (_, Element::Hydrogen) => ElementCategory::ReactiveNonmetal,
+            (
+                _,
+                Element::Boron
+                | Element::Silicon
+                | Element::Germanium
+                | Element::Arsenic
+                | Element::Antimony
+                | Element::Tellurium,
+            ) => ElementCategory::Metalloid,
+            (Some(1), _) => ElementCategory::AlkaliMetal,
+            (Some(2), _) => ElementCategory::AlkalineEarthMetal,
+            (Some(3..=12), _) => ElementCategory::TransitionMetal,
+            (Some(17), _) => ElementCategory::Halogen,
+            (Some(18), _) => ElementCategory::NobleGas,
+            _ if self.is_metal() => ElementCategory::PostTransitionMetal,
+            _ => ElementCategory::ReactiveNonmetal,
+        }
+    }
+
+    /// Metalloids count as non-metals.
+    pub fn is_metal(&self) -> bool {
+        !matches!(
+            self,
+            Element::Hydrogen
+                | Element::Helium
+                | Element::Boron
+                | Element::Carbon
+                | Element::Nitrogen
+                | Element::Oxygen
+                | Element::Fluorine
+                | Element::Neon
+                | Element::Silicon
+                | Element::Phosphorus
+                | Element::Sulfur
+                | Element::Chlorine
+                | Element::Argon
+                | Element::Germanium
+                | Element::Arsenic
+                | Element::Selenium
+                | Element::Bromine
+                | Element::Krypton
+                | Element::Antimony
+                | Element::Tellurium
+                | Element::Iodine
+                | Element::Xenon
+                | Element::Astatine
+                | Element::Radon
+                | Element::Tennessine
+                | Element::Oganesson
+        )
+    }
+}
+
+/// Standard symbol of each element, by atomic number.
+const SYMBOLS: [&str; 118] = [
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S", "Cl",
+    "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge", "As",
+    "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd", "In",
+    "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd", "Tb",
+    "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg", "Tl",
+    "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm", "Bk",
+    "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn", "Nh",
+    "Fl", "Mc", "Lv", "Ts", "Og",
+];
+
+/// Atomic number of the last element in each period.
+const PERIOD_ENDS: [u8; 7] = [2, 10, 18, 36, 54, 86, 118];
+
+/// Picks out elements by their place in the periodic table, for reaction templates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElementFilter {
+    Group(u8),
+    Period(u8),
+    Metal,
+    NonMetal,
+}
+
+impl ElementFilter {
+    pub fn matches(&self, element: &Element) -> bool {
+        match self {
+            ElementFilter::Group(group) => element.group() == Some(*group),
+            ElementFilter::Period(period) => element.period() == *period,
+            ElementFilter::Metal => element.is_metal(),
+            ElementFilter::NonMetal => !element.is_metal(),
+        }
+    }
 }
 
 impl Display for Element {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Which orbital the outermost electrons fill.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Reflect, FromReflect, Sequence)]
+pub enum ElementBlock {
+    S,
+    P,
+    D,
+    F,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Reflect, FromReflect, Sequence)]
+pub enum ElementCategory {
+    AlkaliMetal,
+    AlkalineEarthMetal,
+    Lanthanide,
+    Actinide,
+    TransitionMetal,
+    PostTransitionMetal,
+    Metalloid,
+    ReactiveNonmetal,
+    Halogen,
+    NobleGas,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iron(quantity: u32) -> ItemStack {
+        Element::Iron.to_item_stack(State::Solid, quantity)
+    }
+
+    fn uranium(mass: Option<u16>, quantity: u32) -> ItemStack {
+        ItemStackType::Element(Element::Uranium, State::Solid, mass).to_item_stack(quantity)
+    }
+
+    #[test]
+    fn periodic_table_positions() {
+        assert_eq!(Element::Hydrogen.period(), 1);
+        assert_eq!(Element::Hydrogen.group(), Some(1));
+        assert_eq!(Element::Helium.group(), Some(18));
+        assert_eq!(Element::Boron.group(), Some(13));
+        assert_eq!(Element::Iron.period(), 4);
+        assert_eq!(Element::Iron.group(), Some(8));
+        assert_eq!(Element::Gold.period(), 6);
+        assert_eq!(Element::Gold.group(), Some(11));
+        assert_eq!(Element::Cerium.group(), None);
+        assert_eq!(Element::Lutetium.group(), Some(3));
+        assert_eq!(Element::Lawrencium.group(), Some(3));
+        assert_eq!(Element::Oganesson.period(), 7);
+        assert_eq!(Element::Oganesson.group(), Some(18));
+    }
+
+    #[test]
+    fn periodic_table_blocks() {
+        assert_eq!(Element::Helium.block(), ElementBlock::S);
+        assert_eq!(Element::Sodium.block(), ElementBlock::S);
+        assert_eq!(Element::Carbon.block(), ElementBlock::P);
+        assert_eq!(Element::Copper.block(), ElementBlock::D);
+        assert_eq!(Element::Uranium.block(), ElementBlock::F);
+    }
+
+    #[test]
+    fn periodic_table_categories() {
+        assert_eq!(
+            Element::Hydrogen.category(),
+            ElementCategory::ReactiveNonmetal
+        );
+        assert_eq!(Element::Lithium.category(), ElementCategory::AlkaliMetal);
+        assert_eq!(
+            Element::Calcium.category(),
+            ElementCategory::AlkalineEarthMetal
+        );
+        assert_eq!(Element::Iron.category(), ElementCategory::TransitionMetal);
+        assert_eq!(
+            Element::Aluminium.category(),
+            ElementCategory::PostTransitionMetal
+        );
+        assert_eq!(Element::Silicon.category(), ElementCategory::Metalloid);
+        assert_eq!(Element::Chlorine.category(), ElementCategory::Halogen);
+        assert_eq!(Element::Argon.category(), ElementCategory::NobleGas);
+        assert_eq!(Element::Lanthanum.category(), ElementCategory::Lanthanide);
+        assert_eq!(Element::Lutetium.category(), ElementCategory::Lanthanide);
+        assert_eq!(Element::Uranium.category(), ElementCategory::Actinide);
+    }
+
+    #[test]
+    fn symbols_round_trip() {
+        for element in all::<Element>() {
+            assert_eq!(
+                Element::from_symbol(element.symbol()),
+                Some(element.clone())
+            );
+        }
+        assert_eq!(Element::from_symbol("Fe"), Some(Element::Iron));
+        assert_eq!(Element::from_symbol("fe"), None);
+        assert_eq!(Element::from_symbol("Xx"), None);
+        assert_eq!(Element::Tungsten.to_string(), "W");
+    }
+
+    #[test]
+    fn stack_limit_precedence() {
+        let limits = StackLimits {
+            default: 10,
+            states: StdHashMap::from([(State::Gas, 20)]),
+            items: StdHashMap::from([(iron(1).item_type, 5)]),
+        };
+        assert_eq!(limits.limit(&iron(1).item_type), 5);
+        assert_eq!(
+            limits.limit(&Element::Copper.to_item_stack(State::Solid, 1).item_type),
+            10
+        );
+        assert_eq!(
+            limits.limit(&Element::Oxygen.to_item_stack(State::Gas, 1).item_type),
+            20
+        );
+        assert_eq!(
+            limits.limit(&Energy::Electric.to_item_stack(1).item_type),
+            10
+        );
+    }
+
+    #[test]
+    fn push_overflows_into_new_stacks() {
+        let limits = StackLimits {
+            default: 10,
+            states: StdHashMap::new(),
+            items: StdHashMap::new(),
+        };
+        let mut inventory = Inventory::from(vec![iron(7)]);
+        inventory.push(iron(25), &limits);
+        let quantities = inventory
+            .items
+            .iter()
+            .map(|stack| stack.quantity)
+            .collect::<Vec<_>>();
+        assert_eq!(quantities, vec![10, 10, 10, 2]);
+        assert_eq!(inventory.quantity(&iron(1).item_type), 32);
+        assert!(!inventory.has_room(&iron(1), &limits));
+        assert!(Inventory::default().has_room(&iron(10), &limits));
+    }
+
+    #[test]
+    fn natural_mix_matches_any_isotope() {
+        let natural = uranium(None, 1).item_type;
+        let u235 = uranium(Some(235), 1).item_type;
+        let u238 = uranium(Some(238), 1).item_type;
+        assert!(u235.matches(&natural));
+        assert!(natural.matches(&natural));
+        assert!(!natural.matches(&u235));
+        assert!(!u238.matches(&u235));
+        assert!(!u235.matches(&iron(1).item_type));
+    }
+
+    #[test]
+    fn remove_matching_takes_from_every_isotope() {
+        let mut inventory =
+            Inventory::from(vec![uranium(Some(235), 2), iron(4), uranium(Some(238), 5)]);
+        assert!(inventory.contains_matching(&uranium(None, 7)));
+        inventory.remove_matching(&uranium(None, 4));
+        assert_eq!(inventory.items, vec![iron(4), uranium(Some(238), 3)]);
+        assert!(!inventory.contains_matching(&uranium(None, 4)));
     }
 }
@@ -0,0 +1,229 @@
+use std::{f32::consts::TAU, fmt::Display};
+
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashMap};
+use bevy_prototype_debug_lines::{DebugLines, DebugShapes};
+use enum_iterator::{next_cycle, Sequence};
+
+use crate::{
+    components::BlockStatus,
+    events::{BlockRemoved, ItemTransferred, StallReason},
+    power::{PowerConnection, PowerConsumer, PowerNetworks, PowerPole},
+    statistics::{Period, Statistics},
+    ui::not_typing,
+};
+
+pub struct OverlaysPlugin;
+
+impl Plugin for OverlaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Overlay>()
+            .init_resource::<Flows>()
+            .add_system(overlay_hotkey.run_if(not_typing))
+            .add_system(track_flows)
+            .add_system(flow_overlay)
+            .add_system(status_overlay)
+            .add_system(throughput_overlay)
+            .add_system(power_overlay);
+    }
+}
+
+/// How quickly flow rates follow changes, per second.
+const FLOW_SMOOTHING: f32 = 0.5;
+
+/// Flows slower than this, in units per second, aren't drawn.
+const MIN_FLOW: f32 = 0.01;
+
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// What is drawn over the factory.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Sequence)]
+pub enum Overlay {
+    #[default]
+    None,
+    /// Arrows along every link items moved along, brighter the busier. Fluid through pipes is
+    /// drawn in green.
+    Flow,
+    /// Blocks that can stall, coloured by why they last stopped.
+    Status,
+    /// Blocks coloured from blue to red by how much they made and used in the last minute.
+    Throughput,
+    /// Reach of every power pole and which consumers are short.
+    Power,
+}
+
+impl Display for Overlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flow {
+    /// Units per second, smoothed over the last few seconds.
+    pub rate: f32,
+    pub fluid: bool,
+}
+
+/// What moved from one block to another.
+#[derive(Resource, Default)]
+pub struct Flows {
+    pub rates: HashMap<(Entity, Entity), Flow>,
+}
+
+fn overlay_hotkey(keys: Res<Input<KeyCode>>, mut overlay: ResMut<Overlay>) {
+    if keys.just_pressed(KeyCode::O) {
+        *overlay = next_cycle(&*overlay).unwrap_or_default();
+    }
+}
+
+fn track_flows(
+    mut flows: ResMut<Flows>,
+    mut transferred: EventReader<ItemTransferred>,
+    mut removed: EventReader<BlockRemoved>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    let keep = (1. - FLOW_SMOOTHING * delta).max(0.);
+    flows.rates.values_mut().for_each(|flow| flow.rate *= keep);
+    // moves within a block, like along a belt, have no link to draw
+    for event in transferred.iter().filter(|event| event.from != event.to) {
+        let flow = flows.rates.entry((event.from, event.to)).or_default();
+        flow.rate += FLOW_SMOOTHING * event.item.quantity as f32;
+        flow.fluid = event.item.item_type.is_fluid();
+    }
+    for event in removed.iter() {
+        flows
+            .rates
+            .retain(|(from, to), _| *from != event.entity && *to != event.entity);
+    }
+    flows.rates.retain(|_, flow| flow.rate >= MIN_FLOW);
+}
+
+fn outline(shapes: &mut DebugShapes, aabb: &Aabb, trans: &GlobalTransform, color: Color) {
+    shapes
+        .cuboid()
+        .min_max(
+            trans.transform_point(aabb.min().into()),
+            trans.transform_point(aabb.max().into()),
+        )
+        .color(color)
+        .duration(0.);
+}
+
+fn flow_overlay(
+    overlay: Res<Overlay>,
+    flows: Res<Flows>,
+    query: Query<&GlobalTransform>,
+    mut lines: ResMut<DebugLines>,
+) {
+    if *overlay != Overlay::Flow {
+        return;
+    }
+    let busiest = flows
+        .rates
+        .values()
+        .map(|flow| flow.rate)
+        .fold(0., f32::max);
+    for ((from, to), flow) in flows.rates.iter() {
+        let (Ok(from), Ok(to)) = (query.get(*from), query.get(*to)) else {
+            continue;
+        };
+        let (start, end) = (from.translation(), to.translation());
+        let busy = 0.4 + 0.6 * flow.rate / busiest;
+        let color = if flow.fluid {
+            Color::rgb(0.2, busy, 0.4)
+        } else {
+            Color::rgb(0.2, busy, 1.)
+        };
+        lines.line_colored(start, end, 0., color);
+
+        // arrow head a little short of the end so it isn't buried in the block
+        let direction = (end - start).normalize_or_zero();
+        let tip = end - direction * 0.5;
+        let side = direction.cross(Vec3::Y).normalize_or_zero() * 0.2;
+        lines.line_colored(tip, tip - direction * 0.3 + side, 0., color);
+        lines.line_colored(tip, tip - direction * 0.3 - side, 0., color);
+    }
+}
+
+fn status_color(status: &BlockStatus) -> Color {
+    match &status.stalled {
+        None => Color::GREEN,
+        Some(StallReason::Idle) => Color::GRAY,
+        Some(StallReason::MissingInput(_)) => Color::YELLOW,
+        Some(StallReason::OutputFull) => Color::ORANGE,
+        Some(StallReason::Overheated) => Color::RED,
+        Some(StallReason::NoPower) => Color::PURPLE,
+    }
+}
+
+fn status_overlay(
+    overlay: Res<Overlay>,
+    query: Query<(&Aabb, &GlobalTransform, &BlockStatus)>,
+    mut shapes: ResMut<DebugShapes>,
+) {
+    if *overlay != Overlay::Status {
+        return;
+    }
+    for (aabb, trans, status) in query.iter() {
+        outline(&mut shapes, aabb, trans, status_color(status));
+    }
+}
+
+fn throughput_overlay(
+    overlay: Res<Overlay>,
+    statistics: Res<Statistics>,
+    query: Query<(&Aabb, &GlobalTransform)>,
+    mut shapes: ResMut<DebugShapes>,
+) {
+    if *overlay != Overlay::Throughput {
+        return;
+    }
+    let rates = statistics.block_rates(Period::Minute);
+    let busiest = rates
+        .iter()
+        .map(|rates| rates.produced + rates.consumed)
+        .fold(0., f32::max);
+    if busiest <= 0. {
+        return;
+    }
+    for rates in rates.iter() {
+        let Ok((aabb, trans)) = query.get(rates.key) else {
+            continue;
+        };
+        let heat = (rates.produced + rates.consumed) / busiest;
+        outline(&mut shapes, aabb, trans, Color::rgb(heat, 0.2, 1. - heat));
+    }
+}
+
+fn power_overlay(
+    overlay: Res<Overlay>,
+    networks: Res<PowerNetworks>,
+    pole_query: Query<(&GlobalTransform, &PowerPole, &PowerConnection)>,
+    consumer_query: Query<(&Aabb, &GlobalTransform, &PowerConsumer)>,
+    mut lines: ResMut<DebugLines>,
+    mut shapes: ResMut<DebugShapes>,
+) {
+    if *overlay != Overlay::Power {
+        return;
+    }
+    for (trans, pole, connection) in pole_query.iter() {
+        let satisfaction = networks
+            .get(connection)
+            .map_or(0., |network| network.satisfaction());
+        let color = Color::rgb(1. - satisfaction, satisfaction, 0.2);
+        let centre = trans.translation() * Vec3::new(1., 0., 1.) + Vec3::Y * 0.05;
+        let point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+            centre + Vec3::new(angle.cos(), 0., angle.sin()) * pole.radius
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            lines.line_colored(point(i), point(i + 1), 0., color);
+        }
+    }
+    for (aabb, trans, consumer) in consumer_query.iter() {
+        if consumer.satisfaction < 1. {
+            outline(&mut shapes, aabb, trans, Color::RED);
+        }
+    }
+}
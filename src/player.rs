@@ -13,24 +13,67 @@ use bevy_mod_picking::PickingCameraBundle;
 use enum_iterator::{all, Sequence};
 
 use crate::{
-    blocks::BlockType,
-    components::{self, Block, BlockClicked, Process},
+    blocks::{BlockType, EXTRACTABLE},
+    components::{
+        self, Accumulator, Block, BlockClicked, Converter, PhaseChanger, Process, Source, Splitter,
+    },
+    fluids::{FluidStorage, PipeNetworks, Tank, Valve},
     grid::GridSelectMode,
-    materials::{self, Element, Energy, Inventory, Reaction},
-    reactions::PROCESS_IRON_TO_GOLD,
+    heat::Temperature,
+    inventory::{PlayerInventory, STARTING_KIT},
+    materials::{
+        self, Compound, Element, ElementCategory, Energy, Inventory, ItemStackType, Reaction,
+        StackLimits,
+    },
+    overlays::Overlay,
+    power::{PowerConnection, PowerNetworks},
+    radiation::Radiation,
+    reactions::ReactionRegistry,
+    research::Research,
+    time_controls::{time_controls_ui, TimeControls},
 };
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
+        app.add_state::<GameMode>();
         app.add_startup_system(spawn_player);
+        app.add_startup_system(choose_game_mode);
+        app.add_system(give_starting_kit.in_schedule(OnEnter(GameMode::Survival)));
         app.add_system(dev_ui);
         app.add_system(player_controller);
         app.add_system(player_hotkeys);
     }
 }
 
+/// Sandbox keeps every debug tool and free building. Survival charges build costs and hides
+/// item spawning and the other debug editors, so everything has to be extracted.
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum GameMode {
+    #[default]
+    Sandbox,
+    Survival,
+}
+
+/// Starts in survival when launched with `--survival`, the mode then holds for the whole game.
+fn choose_game_mode(mut next_state: ResMut<NextState<GameMode>>) {
+    if std::env::args().any(|arg| arg == "--survival") {
+        next_state.set(GameMode::Survival);
+    }
+}
+
+fn give_starting_kit(
+    mut query: Query<&mut PlayerInventory, With<Player>>,
+    limits: Res<StackLimits>,
+) {
+    for mut player_inventory in query.iter_mut() {
+        for item in STARTING_KIT.iter() {
+            player_inventory.inventory.push(item.clone(), &limits);
+        }
+    }
+}
+
 #[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum Modes {
     #[default]
@@ -39,15 +82,6 @@ pub enum Modes {
     Destroy,
 }
 
-#[derive(Component)]
-pub struct ModeReadOut;
-
-#[derive(Component)]
-pub struct RotationReadOut;
-
-#[derive(Component)]
-pub struct BlockReadOut;
-
 #[derive(Component)]
 pub struct Player {}
 
@@ -82,6 +116,19 @@ impl Direction {
             Direction::Down => Direction::Up,
         }
     }
+
+    /// Turns clockwise when looking down, up and down have no right and stay as they are.
+    #[inline]
+    pub fn right(&self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+            Direction::Up => Direction::Up,
+            Direction::Down => Direction::Down,
+        }
+    }
 }
 
 impl Display for Direction {
@@ -238,6 +285,7 @@ fn spawn_player(mut commands: Commands) {
         Name::new("Player"),
         Player {},
         SpawnerOptions::default(),
+        PlayerInventory::default(),
         Camera3dBundle {
             transform: Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y),
             camera: Camera {
@@ -253,11 +301,14 @@ fn spawn_player(mut commands: Commands) {
     ));
 }
 
-#[derive(Component)]
-pub struct UICamera;
-
-fn player_hotkeys(keys: Res<Input<KeyCode>>, mut query: Query<&mut SpawnerOptions, With<Player>>) {
+fn player_hotkeys(
+    keys: Res<Input<KeyCode>>,
+    mut query: Query<&mut SpawnerOptions, With<Player>>,
+    game_mode: Res<State<GameMode>>,
+    research: Res<Research>,
+) {
     for mut ele in query.iter_mut() {
+        let previous = ele.block_selection;
         if keys.just_pressed(KeyCode::Key1) {
             ele.block_selection = BlockType::Debug;
         } else if keys.just_pressed(KeyCode::Key2) {
@@ -270,6 +321,12 @@ fn player_hotkeys(keys: Res<Input<KeyCode>>, mut query: Query<&mut SpawnerOption
             ele.block_selection = BlockType::Storage;
         } else if keys.just_pressed(KeyCode::Key6) {
             ele.block_selection = BlockType::Grabber;
+        } else if keys.just_pressed(KeyCode::Key7) {
+            ele.block_selection = BlockType::Generator;
+        } else if keys.just_pressed(KeyCode::Key8) {
+            ele.block_selection = BlockType::PowerPole;
+        } else if keys.just_pressed(KeyCode::Key9) {
+            ele.block_selection = BlockType::Extractor;
         } else if keys.just_pressed(KeyCode::R) {
             ele.block_rotation = match ele.block_rotation {
                 Direction::North => Direction::East,
@@ -294,6 +351,13 @@ fn player_hotkeys(keys: Res<Input<KeyCode>>, mut query: Query<&mut SpawnerOption
             ele.player_mode = Modes::Overview;
             ele.grid_select_mode = GridSelectMode::Block;
         }
+
+        if game_mode.0 == GameMode::Survival
+            && !(ele.block_selection.is_buildable()
+                && research.is_block_unlocked(&ele.block_selection))
+        {
+            ele.block_selection = previous;
+        }
     }
 }
 
@@ -302,20 +366,63 @@ struct UiState {
     selected_quantity: u32,
     selected_element: Element,
     selected_state: materials::State,
+    selected_isotope: Option<u16>,
+    selected_container: bool,
     selected_energy: Energy,
+    selected_compound: Compound,
     selected_reaction: Option<Reaction>,
+    accepts_element: Element,
+    accepts_state: materials::State,
+    element_search: String,
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn dev_ui(
     mut egui_ctx: EguiContexts,
-    mut player_query: Query<&mut SpawnerOptions, With<Player>>,
+    mut player_query: Query<(&mut SpawnerOptions, &mut PlayerInventory), With<Player>>,
+    game_mode: Res<State<GameMode>>,
     block_selected_query: Query<(&Block, Entity), With<BlockClicked>>,
     mut process_selected_query: Query<&mut Process, With<BlockClicked>>,
     mut input_selected_query: Query<&mut components::Input, With<BlockClicked>>,
     mut output_selected_query: Query<&mut components::Output, With<BlockClicked>>,
+    (
+        converter_selected_query,
+        mut accumulator_selected_query,
+        phase_selected_query,
+        mut splitter_selected_query,
+    ): (
+        Query<&Converter, With<BlockClicked>>,
+        Query<&mut Accumulator, With<BlockClicked>>,
+        Query<&PhaseChanger, With<BlockClicked>>,
+        Query<&mut Splitter, With<BlockClicked>>,
+    ),
+    (power_selected_query, power_networks): (
+        Query<&PowerConnection, With<BlockClicked>>,
+        Res<PowerNetworks>,
+    ),
+    (radiation_selected_query, temperature_selected_query): (
+        Query<&Radiation, With<BlockClicked>>,
+        Query<&Temperature, With<BlockClicked>>,
+    ),
+    (fluid_selected_query, mut valve_selected_query, pipe_networks, mut source_selected_query): (
+        Query<(&FluidStorage, Option<&Tank>), With<BlockClicked>>,
+        Query<&mut Valve, With<BlockClicked>>,
+        Res<PipeNetworks>,
+        Query<&mut Source, With<BlockClicked>>,
+    ),
+    (reaction_registry, research, mut overlay, limits): (
+        Res<ReactionRegistry>,
+        Res<Research>,
+        ResMut<Overlay>,
+        Res<StackLimits>,
+    ),
+    (mut time_controls, mut time): (ResMut<TimeControls>, ResMut<Time>),
     mut ui_state: Local<UiState>,
 ) {
-    let Ok(mut spawn_options) = player_query.get_single_mut() else { return; };
+    let Ok((mut spawn_options, mut player_inventory)) = player_query.get_single_mut() else {
+        return;
+    };
+    let sandbox = game_mode.0 == GameMode::Sandbox;
 
     egui::SidePanel::right("selected_block_panel")
         .default_width(200.0)
@@ -332,11 +439,35 @@ fn dev_ui(
                     "Rotation (R)",
                     &mut spawn_options.block_rotation,
                 );
-                enum_dropdown::<BlockType>(
+                enum_dropdown_where::<BlockType>(
                     ui,
                     "bt".to_string(),
                     "Block (Num Keys)",
                     &mut spawn_options.block_selection,
+                    |block_type| {
+                        sandbox
+                            || (block_type.is_buildable() && research.is_block_unlocked(block_type))
+                    },
+                );
+                enum_dropdown::<Overlay>(ui, "overlay".to_string(), "Overlay (O)", &mut overlay);
+                time_controls_ui(ui, &mut time_controls, &mut time);
+                let cost = spawn_options.block_selection.build_cost();
+                if !cost.is_empty() {
+                    ui.label(format!(
+                        "Cost: {}",
+                        cost.iter().map(|item| item.to_string()).collect::<String>()
+                    ));
+                }
+            });
+            ui.group(|ui| {
+                ui.heading("Player Inventory");
+                inventory_table(
+                    ui,
+                    &mut ui_state,
+                    "player".to_string(),
+                    &mut player_inventory.inventory,
+                    sandbox,
+                    &limits,
                 );
             });
             block_selected_query.iter().for_each(|(block, ent)| {
@@ -345,6 +476,122 @@ fn dev_ui(
                     ui.separator();
                     ui.label(format!("Block Type: {:?}", block.block_type));
                     ui.label(format!("Block Rotation: {:?}", block.direction));
+                    if let Ok(temperature) = temperature_selected_query.get(ent) {
+                        let text = format!(
+                            "Temperature: {:.0}K / {:.0}K",
+                            temperature.kelvin, temperature.max
+                        );
+                        if temperature.is_overheated() {
+                            ui.colored_label(egui::Color32::RED, format!("{} Overheated", text));
+                        } else {
+                            ui.label(text);
+                        }
+                    }
+                    if let Ok(radiation) = radiation_selected_query.get(ent) {
+                        ui.label(format!("Radiation: {:.2}", radiation.level));
+                    }
+
+                    if let Ok(converter) = converter_selected_query.get(ent) {
+                        ui.heading("Converter");
+                        ui.label(format!(
+                            "{} -> {} ({:.0}%)",
+                            converter.input,
+                            converter.output,
+                            converter.efficiency * 100.
+                        ));
+                        ui.label(format!(
+                            "{} in, {} out, {} lost as {} per cycle",
+                            converter.rate,
+                            converter.output_per_cycle(),
+                            converter.loss_per_cycle(),
+                            Energy::Thermal
+                        ));
+                        ui.add(egui::ProgressBar::new(converter.timer.percent()));
+                    }
+
+                    if let Ok(mut accumulator) = accumulator_selected_query.get_mut(ent) {
+                        ui.heading("Accumulator");
+                        // only sandbox can switch what an accumulator holds
+                        if accumulator.charge > 0. || !sandbox {
+                            ui.label(format!("Stores: {}", accumulator.energy));
+                        } else {
+                            enum_dropdown::<Energy>(
+                                ui,
+                                "accumulator_energy".to_string(),
+                                "Stores",
+                                &mut accumulator.energy,
+                            );
+                        }
+                        ui.add(egui::ProgressBar::new(accumulator.percent()).text(format!(
+                            "{:.0} / {:.0}",
+                            accumulator.charge, accumulator.capacity
+                        )));
+                        ui.label(format!(
+                            "Charge: {:.0}/s, Discharge: {:.0}/s",
+                            accumulator.charge_rate, accumulator.discharge_rate
+                        ));
+                    }
+
+                    if let Ok(changer) = phase_selected_query.get(ent) {
+                        ui.heading("Phase Change");
+                        ui.label(format!(
+                            "{} -> {} using {}",
+                            changer.from, changer.to, changer.energy
+                        ));
+                        ui.add(egui::ProgressBar::new(changer.timer.percent()));
+                    }
+
+                    if let Ok((storage, tank)) = fluid_selected_query.get(ent) {
+                        ui.heading("Fluid");
+                        ui.label(match &storage.fluid {
+                            Some(fluid) => format!("{}", fluid),
+                            None => "Empty".to_string(),
+                        });
+                        ui.add(
+                            egui::ProgressBar::new(storage.fill().min(1.))
+                                .text(format!("{} / {}", storage.amount, storage.capacity)),
+                        );
+                        if let Some(network) = pipe_networks.get(storage) {
+                            ui.label(format!(
+                                "Network: {} segments, {:.0}L",
+                                network.segments.len(),
+                                network.volume
+                            ));
+                            ui.label(format!(
+                                "Pressure: {:.0}kPa at {:.0}K",
+                                network.pressure, network.temperature
+                            ));
+                        }
+                        if let Some(tank) = tank {
+                            ui.label(format!("Rated to: {:.0}kPa", tank.pressure_rating));
+                        }
+                    }
+                    if let Ok(mut valve) = valve_selected_query.get_mut(ent) {
+                        ui.checkbox(&mut valve.open, "Valve Open");
+                    }
+
+                    if let Ok(connection) = power_selected_query.get(ent) {
+                        ui.heading("Power");
+                        match power_networks.get(connection) {
+                            Some(network) => {
+                                ui.label(format!("Supply: {:.1}", network.supply));
+                                ui.label(format!("Demand: {:.1}", network.demand));
+                                if network.capacity > 0. {
+                                    ui.label(format!(
+                                        "Stored: {:.0} / {:.0}",
+                                        network.stored, network.capacity
+                                    ));
+                                }
+                                ui.add(
+                                    egui::ProgressBar::new(network.satisfaction())
+                                        .text("Satisfaction"),
+                                );
+                            }
+                            None => {
+                                ui.label("Not connected");
+                            }
+                        }
+                    }
 
                     if let Ok(mut process) = process_selected_query.get_mut(ent) {
                         ui.heading("Process");
@@ -366,27 +613,112 @@ fn dev_ui(
                                         None,
                                         "None",
                                     );
+                                    for reaction in
+                                        reaction_registry.reactions.iter().filter(|reaction| {
+                                            sandbox || research.is_reaction_unlocked(reaction)
+                                        })
+                                    {
+                                        ui.selectable_value(
+                                            &mut ui_state.selected_reaction,
+                                            Some(reaction.clone()),
+                                            reaction.to_string(),
+                                        );
+                                    }
+                                });
+                        }
+                        if let Some(selected) = ui_state.selected_reaction.as_ref() {
+                            if process.reaction.as_ref() != Some(selected) {
+                                process.set_reaction(selected);
+                            }
+                        }
+                    }
+
+                    if let Ok(mut source) = source_selected_query.get_mut(ent) {
+                        ui.heading("Extractor");
+                        let current = source
+                            .source
+                            .as_ref()
+                            .and_then(|stack| stack.item_type.element().cloned());
+                        let mut selected = current.clone();
+                        egui::ComboBox::from_id_source("extractor_source")
+                            .selected_text(
+                                selected
+                                    .as_ref()
+                                    .map_or("None".to_string(), |element| format!("{:?}", element)),
+                            )
+                            .show_ui(ui, |ui| {
+                                for element in EXTRACTABLE.iter() {
                                     ui.selectable_value(
-                                        &mut ui_state.selected_reaction,
-                                        Some(PROCESS_IRON_TO_GOLD.clone()),
-                                        format!("{}", PROCESS_IRON_TO_GOLD.clone()),
+                                        &mut selected,
+                                        Some(element.clone()),
+                                        format!("{:?}", element),
                                     );
-                                });
+                                }
+                            });
+                        if selected != current {
+                            source.source = selected
+                                .map(|element| element.to_item_stack(materials::State::Solid, 1));
                         }
-                        if ui_state.selected_reaction.is_some()
-                            && process.reaction != ui_state.selected_reaction
-                        {
-                            process.set_reaction(ui_state.selected_reaction.as_ref().unwrap());
+                    }
+
+                    if let Ok(mut splitter) = splitter_selected_query.get_mut(ent) {
+                        ui.heading("Splitter");
+                        let mut filtered = splitter.filter.is_some();
+                        ui.checkbox(&mut filtered, "Filter");
+                        if !filtered {
+                            splitter.filter = None;
+                        } else {
+                            let filter = splitter.filter.get_or_insert(Element::default());
+                            element_picker(
+                                ui,
+                                "splitter-el".to_string(),
+                                &mut ui_state.element_search,
+                                filter,
+                            );
                         }
                     }
 
                     if let Ok(mut input) = input_selected_query.get_mut(ent) {
                         ui.heading("Input");
+                        ui.label(match &input.accepts {
+                            Some(accepts) => format!("Accepts {}", accepts),
+                            None => "Accepts anything".to_string(),
+                        });
+                        ui.add_visible_ui(sandbox, |ui| {
+                            ui.horizontal(|ui| {
+                                let state = &mut *ui_state;
+                                element_picker(
+                                    ui,
+                                    "accepts-el".to_string(),
+                                    &mut state.element_search,
+                                    &mut state.accepts_element,
+                                );
+                                enum_dropdown::<materials::State>(
+                                    ui,
+                                    "accepts-st".to_string(),
+                                    "State",
+                                    &mut state.accepts_state,
+                                );
+                                if ui.button("Set").clicked() {
+                                    input.accepts = Some(
+                                        state
+                                            .accepts_element
+                                            .clone()
+                                            .to_item_stack(state.accepts_state.clone(), 1),
+                                    );
+                                }
+                                if ui.button("Clear").clicked() {
+                                    input.accepts = None;
+                                }
+                            })
+                        });
                         inventory_table(
                             ui,
                             &mut ui_state,
                             "input".to_string(),
                             &mut input.inventory,
+                            sandbox,
+                            &limits,
                         );
                     }
                     if let Ok(mut output) = output_selected_query.get_mut(ent) {
@@ -396,7 +728,14 @@ fn dev_ui(
                             &mut ui_state,
                             "output".to_string(),
                             &mut output.inventory,
+                            sandbox,
+                            &limits,
                         );
+                        if ui.button("Take").clicked() {
+                            while let Some(item) = output.inventory.pop() {
+                                player_inventory.inventory.push(item, &limits);
+                            }
+                        }
                     }
                 });
             });
@@ -409,6 +748,8 @@ fn inventory_table(
     ui_state: &mut Local<UiState>,
     id: String,
     inventory: &mut Inventory,
+    sandbox: bool,
+    limits: &StackLimits,
 ) {
     ui.horizontal(|ui| {
         ui.label("Item");
@@ -423,18 +764,30 @@ fn inventory_table(
             ui.label(format!("{}", stack.quantity));
         });
     }
+    // in survival everything has to be extracted
+    if !sandbox {
+        return;
+    }
     ui.collapsing(format!("Add {} Item", id), |ui| {
+        let element = ui_state.selected_element.clone();
+        let state = ui_state.selected_state.clone();
+        let limit = limits.limit(&if ui_state.selected_container {
+            ItemStackType::Container(element, state, ui_state.selected_isotope)
+        } else {
+            ItemStackType::Element(element, state, ui_state.selected_isotope)
+        });
         ui.add(
             egui::DragValue::new(&mut ui_state.selected_quantity)
                 .speed(0.1)
-                .clamp_range(1..=64),
+                .clamp_range(1..=limit),
         );
         ui.horizontal(|ui| {
-            enum_dropdown::<Element>(
+            let state = &mut **ui_state;
+            element_picker(
                 ui,
                 format!("{}-el", id),
-                "Element",
-                &mut ui_state.selected_element,
+                &mut state.element_search,
+                &mut state.selected_element,
             );
             enum_dropdown::<materials::State>(
                 ui,
@@ -443,13 +796,37 @@ fn inventory_table(
                 &mut ui_state.selected_state,
             );
             if ui.button("Add").clicked() {
-                inventory.push(
-                    ui_state
-                        .selected_element
-                        .clone()
-                        .to_item_stack(ui_state.selected_state.clone(), ui_state.selected_quantity),
-                );
+                let element = ui_state.selected_element.clone();
+                let state = ui_state.selected_state.clone();
+                let item_type = if ui_state.selected_container {
+                    ItemStackType::Container(element, state, ui_state.selected_isotope)
+                } else {
+                    ItemStackType::Element(element, state, ui_state.selected_isotope)
+                };
+                inventory.push(item_type.to_item_stack(ui_state.selected_quantity), limits);
+            }
+        });
+        let element = &ui_state.selected_element;
+        ui.label(format!(
+            "{:?}, period {}, group {}, {:?}-block, {:?}",
+            element,
+            element.period(),
+            element
+                .group()
+                .map_or("-".to_string(), |group| group.to_string()),
+            element.block(),
+            element.category(),
+        ));
+        ui.horizontal(|ui| {
+            let mut isotope = ui_state.selected_isotope.is_some();
+            ui.checkbox(&mut isotope, "Isotope");
+            if !isotope {
+                ui_state.selected_isotope = None;
+            } else {
+                let mass = ui_state.selected_isotope.get_or_insert(1);
+                ui.add(egui::DragValue::new(mass).clamp_range(1..=300));
             }
+            ui.checkbox(&mut ui_state.selected_container, "Container");
         });
         ui.horizontal(|ui| {
             enum_dropdown::<Energy>(
@@ -464,24 +841,143 @@ fn inventory_table(
                         .selected_energy
                         .clone()
                         .to_item_stack(ui_state.selected_quantity),
+                    limits,
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            enum_dropdown::<Compound>(
+                ui,
+                format!("{}-co", id),
+                "Compound",
+                &mut ui_state.selected_compound,
+            );
+            if ui.button("Add").clicked() {
+                inventory.push(
+                    ui_state
+                        .selected_compound
+                        .clone()
+                        .to_item_stack(ui_state.selected_state.clone(), ui_state.selected_quantity),
+                    limits,
                 );
             }
         });
     });
 }
 
+/// Periodic table laid out by period and group with the f-block underneath, coloured by
+/// category. Elements not matching `search` by name or symbol are greyed out, and pressing
+/// enter on an exact symbol picks it.
+pub fn element_picker(ui: &mut egui::Ui, id: String, search: &mut String, value: &mut Element) {
+    ui.label("Element");
+    ui.menu_button(format!("{} {:?}", value, value), |ui| {
+        let response = ui.text_edit_singleline(search);
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            if let Some(element) = Element::from_symbol(search.trim()) {
+                *value = element;
+                ui.close_menu();
+            }
+        }
+
+        let query = search.trim().to_lowercase();
+        let elements = all::<Element>().collect::<Vec<_>>();
+        let mut element_button = |ui: &mut egui::Ui, element: &Element| {
+            let matches = query.is_empty()
+                || format!("{:?}", element).to_lowercase().contains(&query)
+                || element.symbol().to_lowercase() == query;
+            let fill = if matches {
+                category_color(&element.category())
+            } else {
+                egui::Color32::from_gray(60)
+            };
+            let mut button = egui::Button::new(
+                egui::RichText::new(element.symbol()).color(egui::Color32::BLACK),
+            )
+            .fill(fill)
+            .min_size(egui::vec2(26., 22.));
+            if element == value {
+                button = button.stroke(egui::Stroke::new(2., egui::Color32::WHITE));
+            }
+            let clicked = ui
+                .add(button)
+                .on_hover_text(format!("{:?} ({})", element, element.atomic_number()))
+                .clicked();
+            if clicked {
+                *value = element.clone();
+                ui.close_menu();
+            }
+        };
+
+        egui::Grid::new(id)
+            .spacing(egui::vec2(2., 2.))
+            .show(ui, |ui| {
+                for period in 1..=7 {
+                    for group in 1..=18 {
+                        match elements.iter().find(|element| {
+                            element.period() == period && element.group() == Some(group)
+                        }) {
+                            Some(element) => element_button(ui, element),
+                            None => {
+                                ui.label("");
+                            }
+                        }
+                    }
+                    ui.end_row();
+                }
+                ui.end_row();
+                for period in 6..=7 {
+                    ui.label("");
+                    ui.label("");
+                    for element in elements
+                        .iter()
+                        .filter(|element| element.period() == period && element.group().is_none())
+                    {
+                        element_button(ui, element);
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+fn category_color(category: &ElementCategory) -> egui::Color32 {
+    match category {
+        ElementCategory::AlkaliMetal => egui::Color32::from_rgb(255, 102, 102),
+        ElementCategory::AlkalineEarthMetal => egui::Color32::from_rgb(255, 222, 173),
+        ElementCategory::Lanthanide => egui::Color32::from_rgb(255, 191, 255),
+        ElementCategory::Actinide => egui::Color32::from_rgb(255, 153, 204),
+        ElementCategory::TransitionMetal => egui::Color32::from_rgb(255, 192, 192),
+        ElementCategory::PostTransitionMetal => egui::Color32::from_rgb(204, 204, 204),
+        ElementCategory::Metalloid => egui::Color32::from_rgb(204, 204, 153),
+        ElementCategory::ReactiveNonmetal => egui::Color32::from_rgb(160, 255, 160),
+        ElementCategory::Halogen => egui::Color32::from_rgb(255, 255, 153),
+        ElementCategory::NobleGas => egui::Color32::from_rgb(192, 255, 255),
+    }
+}
+
 #[inline]
-fn enum_dropdown<T: Sequence + PartialEq + Display + Clone + Debug>(
+pub fn enum_dropdown<T: Sequence + PartialEq + Display + Clone + Debug>(
+    ui: &mut egui::Ui,
+    id: String,
+    label: &str,
+    value: &mut T,
+) {
+    enum_dropdown_where(ui, id, label, value, |_| true);
+}
+
+/// Like [`enum_dropdown`], only offering the options `filter` lets through.
+pub fn enum_dropdown_where<T: Sequence + PartialEq + Display + Clone + Debug>(
     ui: &mut egui::Ui,
     id: String,
     label: &str,
     value: &mut T,
+    filter: impl Fn(&T) -> bool,
 ) {
     ui.label(label);
     egui::ComboBox::from_id_source(id)
         .selected_text(format!("{}", value))
         .show_ui(ui, |ui| {
-            for option in all::<T>().collect::<Vec<T>>() {
+            for option in all::<T>().filter(|option| filter(option)) {
                 ui.selectable_value(value, option.clone(), format!("{}", option));
             }
         });
@@ -0,0 +1,245 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    components::Accumulator, heat::Temperature, materials::Energy,
+    time_controls::simulation_running,
+};
+
+pub struct PowerPlugin;
+
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Generator>()
+            .register_type::<PowerPole>()
+            .register_type::<PowerConsumer>()
+            .register_type::<PowerConnection>()
+            .init_resource::<PowerNetworks>()
+            .add_system(power_network_system.run_if(simulation_running));
+    }
+}
+
+/// Produces power into whichever network it is connected to, in units per second.
+#[derive(Component, Reflect)]
+pub struct Generator {
+    pub output: f32,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self { output: 100. }
+    }
+}
+
+/// Links every power block within `radius` of it, including other poles, into one network.
+#[derive(Component, Reflect)]
+pub struct PowerPole {
+    pub radius: f32,
+}
+
+impl Default for PowerPole {
+    fn default() -> Self {
+        Self { radius: 5. }
+    }
+}
+
+/// Draws `demand` units per second from its network. `satisfaction` is the fraction of that
+/// demand the network could supply last frame and is what machines scale their speed by.
+/// Consumers out of reach of every pole get nothing and stop.
+#[derive(Component, Reflect)]
+pub struct PowerConsumer {
+    pub demand: f32,
+    pub satisfaction: f32,
+}
+
+impl PowerConsumer {
+    pub fn new(demand: f32) -> Self {
+        Self {
+            demand,
+            satisfaction: 0.,
+        }
+    }
+}
+
+#[derive(Component, Default, Reflect)]
+pub struct PowerConnection {
+    pub network: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PowerNetwork {
+    pub poles: Vec<Entity>,
+    pub supply: f32,
+    pub demand: f32,
+    pub stored: f32,
+    pub capacity: f32,
+}
+
+impl PowerNetwork {
+    pub fn satisfaction(&self) -> f32 {
+        if self.demand <= 0. {
+            return 1.;
+        }
+        (self.supply / self.demand).min(1.)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PowerNetworks {
+    pub networks: Vec<PowerNetwork>,
+}
+
+impl PowerNetworks {
+    pub fn get(&self, connection: &PowerConnection) -> Option<&PowerNetwork> {
+        connection.network.and_then(|i| self.networks.get(i))
+    }
+}
+
+/// Poles bucketed by position, buckets as wide as the longest reach so every pole that could
+/// connect to a point is in its bucket or one of those around it.
+struct PoleBuckets {
+    size: f32,
+    buckets: HashMap<IVec3, Vec<usize>>,
+}
+
+impl PoleBuckets {
+    fn new(poles: &[(Entity, Vec3, f32)]) -> Self {
+        let size = poles
+            .iter()
+            .map(|(_, _, radius)| *radius)
+            .fold(1., f32::max);
+        let mut buckets: HashMap<IVec3, Vec<usize>> = HashMap::new();
+        for (index, (_, position, _)) in poles.iter().enumerate() {
+            buckets
+                .entry((*position / size).floor().as_ivec3())
+                .or_default()
+                .push(index);
+        }
+        Self { size, buckets }
+    }
+
+    /// Indices of the poles close enough to `position` that they might reach it.
+    fn near(&self, position: Vec3) -> impl Iterator<Item = usize> + '_ {
+        let bucket = (position / self.size).floor().as_ivec3();
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .filter_map(move |offset| self.buckets.get(&(bucket + offset)))
+            .flatten()
+            .copied()
+    }
+}
+
+pub fn power_network_system(
+    mut networks: ResMut<PowerNetworks>,
+    pole_query: Query<(Entity, &GlobalTransform, &PowerPole, Option<&Temperature>)>,
+    mut connection_query: Query<(Entity, &GlobalTransform, &mut PowerConnection)>,
+    generator_query: Query<&Generator>,
+    mut consumer_query: Query<&mut PowerConsumer>,
+    mut accumulator_query: Query<&mut Accumulator>,
+    time: Res<Time>,
+) {
+    // an overheated pole stops linking anything until it cools down
+    let poles = pole_query
+        .iter()
+        .filter(|(_, _, _, temperature)| {
+            !temperature.is_some_and(|temperature| temperature.is_overheated())
+        })
+        .map(|(ent, trans, pole, _)| (ent, trans.translation(), pole.radius))
+        .collect::<Vec<_>>();
+
+    // flood fill the poles into networks, two poles connect when either can reach the other
+    let buckets = PoleBuckets::new(&poles);
+    let mut pole_network: Vec<Option<usize>> = vec![None; poles.len()];
+    let mut result: Vec<PowerNetwork> = vec![];
+    for start in 0..poles.len() {
+        if pole_network[start].is_some() {
+            continue;
+        }
+        let network = result.len();
+        result.push(PowerNetwork::default());
+        pole_network[start] = Some(network);
+        let mut open = vec![start];
+        while let Some(current) = open.pop() {
+            result[network].poles.push(poles[current].0);
+            for next in buckets.near(poles[current].1) {
+                if pole_network[next].is_some() {
+                    continue;
+                }
+                let reach = poles[current].2.max(poles[next].2);
+                if poles[current].1.distance(poles[next].1) <= reach {
+                    pole_network[next] = Some(network);
+                    open.push(next);
+                }
+            }
+        }
+    }
+
+    for (ent, trans, mut connection) in connection_query.iter_mut() {
+        let position = trans.translation();
+        connection.network = buckets
+            .near(position)
+            .find(|index| {
+                let (pole, pole_position, radius) = poles[*index];
+                pole == ent || pole_position.distance(position) <= radius
+            })
+            .and_then(|index| pole_network[index]);
+    }
+
+    for (ent, _, connection) in connection_query.iter() {
+        let Some(network) = connection.network else {
+            continue;
+        };
+        if let Ok(generator) = generator_query.get(ent) {
+            result[network].supply += generator.output;
+        }
+        if let Ok(consumer) = consumer_query.get(ent) {
+            result[network].demand += consumer.demand;
+        }
+    }
+
+    // accumulators soak up any surplus and cover any shortfall, within their rate limits
+    let delta = time.delta_seconds();
+    let mut balance = result
+        .iter()
+        .map(|network| network.supply - network.demand)
+        .collect::<Vec<_>>();
+    for (ent, _, connection) in connection_query.iter() {
+        let Some(network) = connection.network else {
+            continue;
+        };
+        let Ok(mut accumulator) = accumulator_query.get_mut(ent) else {
+            continue;
+        };
+        if accumulator.energy != Energy::Electric {
+            continue;
+        }
+
+        if delta > 0. && balance[network] > 0. {
+            let rate = balance[network]
+                .min(accumulator.charge_rate)
+                .min((accumulator.capacity - accumulator.charge) / delta);
+            accumulator.charge += rate * delta;
+            balance[network] -= rate;
+        } else if delta > 0. && balance[network] < 0. {
+            let rate = (-balance[network])
+                .min(accumulator.discharge_rate)
+                .min(accumulator.charge / delta);
+            accumulator.charge -= rate * delta;
+            balance[network] += rate;
+            result[network].supply += rate;
+        }
+
+        result[network].stored += accumulator.charge;
+        result[network].capacity += accumulator.capacity;
+    }
+
+    for (ent, _, connection) in connection_query.iter() {
+        let Ok(mut consumer) = consumer_query.get_mut(ent) else {
+            continue;
+        };
+        consumer.satisfaction = connection
+            .network
+            .map_or(0., |network| result[network].satisfaction());
+    }
+
+    networks.networks = result;
+}
@@ -0,0 +1,225 @@
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{HashMap, HashSet},
+};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+use serde::Deserialize;
+
+use crate::{
+    blocks::BlockType,
+    config::{RonAsset, RonAssetPlugin},
+    events::{BlockPlaced, ItemProduced},
+    inventory::PlayerInventory,
+    materials::{ItemStack, ItemStackType, StackLimits},
+    player::Player,
+    research::Research,
+};
+
+pub struct QuestsPlugin;
+
+impl Plugin for QuestsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Quests>()
+            .add_plugin(RonAssetPlugin::<QuestBook>::new(QUESTS_PATH, "quests.ron"))
+            .add_system(quest_tracking_system)
+            .add_system(objectives_ui);
+    }
+}
+
+const QUESTS_PATH: &str = "config/default.quests.ron";
+
+/// Rates are averaged over this many seconds.
+const RATE_WINDOW: f32 = 60.;
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Condition {
+    /// Make `quantity` of anything matching `item` in total.
+    Produce {
+        item: ItemStackType,
+        quantity: u32,
+    },
+    Place {
+        block: BlockType,
+        count: u32,
+    },
+    /// Make `per_minute` of anything matching `item` over the last minute.
+    Rate {
+        item: ItemStackType,
+        per_minute: f32,
+    },
+}
+
+impl Condition {
+    fn target(&self) -> f32 {
+        match self {
+            Condition::Produce { quantity, .. } => *quantity as f32,
+            Condition::Place { count, .. } => *count as f32,
+            Condition::Rate { per_minute, .. } => *per_minute,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Reward {
+    Items(Vec<ItemStack>),
+    /// Id of a technology to mark as researched.
+    Unlock(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Quest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Ids of the quests that have to be completed first.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    pub condition: Condition,
+    #[serde(default)]
+    pub rewards: Vec<Reward>,
+}
+
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "3e8d1f6a-52c4-4b9e-a07d-6f1c2b8e4d95"]
+pub struct QuestBook {
+    pub quests: Vec<Quest>,
+}
+
+impl RonAsset for QuestBook {
+    type Target = Quests;
+
+    fn apply(&self, quests: &mut Quests) {
+        quests.quests = self.quests.clone();
+        quests.loaded = true;
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Quests {
+    pub quests: Vec<Quest>,
+    pub loaded: bool,
+    pub completed: HashSet<String>,
+    /// Towards each quest's condition, in the same units as [`Condition::target`].
+    pub progress: HashMap<String, f32>,
+    /// Everything produced within the last [`RATE_WINDOW`], with when it was produced.
+    recent: Vec<(f32, ItemStack)>,
+}
+
+impl Quests {
+    pub fn is_active(&self, quest: &Quest) -> bool {
+        !self.completed.contains(&quest.id)
+            && quest.requires.iter().all(|id| self.completed.contains(id))
+    }
+
+    pub fn progress(&self, quest: &Quest) -> f32 {
+        *self.progress.get(&quest.id).unwrap_or(&0.)
+    }
+}
+
+fn quest_tracking_system(
+    mut quests: ResMut<Quests>,
+    mut produced: EventReader<ItemProduced>,
+    mut placed: EventReader<BlockPlaced>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    mut research: ResMut<Research>,
+    limits: Res<StackLimits>,
+    time: Res<Time>,
+) {
+    let quests = &mut *quests;
+    let now = time.elapsed_seconds();
+    let active = quests
+        .quests
+        .iter()
+        .filter(|quest| quests.is_active(quest))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for event in produced.iter() {
+        quests.recent.push((now, event.item.clone()));
+        for quest in active.iter() {
+            if let Condition::Produce { item, .. } = &quest.condition {
+                if event.item.item_type.matches(item) {
+                    *quests.progress.entry(quest.id.clone()).or_default() +=
+                        event.item.quantity as f32;
+                }
+            }
+        }
+    }
+    quests.recent.retain(|(at, _)| now - at <= RATE_WINDOW);
+
+    for event in placed.iter() {
+        for quest in active.iter() {
+            if let Condition::Place { block, .. } = &quest.condition {
+                if event.block_type == *block {
+                    *quests.progress.entry(quest.id.clone()).or_default() += 1.;
+                }
+            }
+        }
+    }
+
+    for quest in active.iter() {
+        if let Condition::Rate { item, .. } = &quest.condition {
+            let made = quests
+                .recent
+                .iter()
+                .filter(|(_, stack)| stack.item_type.matches(item))
+                .map(|(_, stack)| stack.quantity as f32)
+                .sum::<f32>();
+            quests
+                .progress
+                .insert(quest.id.clone(), made * 60. / RATE_WINDOW);
+        }
+
+        if quests.progress(quest) < quest.condition.target() {
+            continue;
+        }
+        info!("Completed {}", quest.name);
+        quests.completed.insert(quest.id.clone());
+        for reward in quest.rewards.iter() {
+            match reward {
+                Reward::Items(items) => {
+                    let Ok(mut player_inventory) = player_query.get_single_mut() else {
+                        continue;
+                    };
+                    for item in items {
+                        player_inventory.inventory.push(item.clone(), &limits);
+                    }
+                }
+                Reward::Unlock(id) => {
+                    research.unlock(id);
+                }
+            }
+        }
+    }
+}
+
+fn objectives_ui(mut egui_ctx: EguiContexts, quests: Res<Quests>) {
+    egui::Window::new("Objectives")
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(10., 10.))
+        .resizable(false)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            if !quests.loaded {
+                ui.label("Loading objectives...");
+                return;
+            }
+            let mut any = false;
+            for quest in quests.quests.iter().filter(|quest| quests.is_active(quest)) {
+                any = true;
+                ui.strong(&quest.name);
+                if !quest.description.is_empty() {
+                    ui.label(&quest.description);
+                }
+                let target = quest.condition.target();
+                let progress = quests.progress(quest);
+                ui.add(
+                    egui::ProgressBar::new((progress / target).min(1.))
+                        .text(format!("{:.0} / {:.0}", progress, target)),
+                );
+            }
+            if !any {
+                ui.label("All objectives complete");
+            }
+        });
+}
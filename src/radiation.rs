@@ -0,0 +1,396 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+use lazy_static::lazy_static;
+
+use crate::{
+    components::{self, Output},
+    events::{ItemConsumed, ItemProduced},
+    inventory::PlayerInventory,
+    materials::{Element, Energy, Inventory, ItemStack, ItemStackType, StackLimits},
+    time_controls::simulation_running,
+};
+
+pub struct RadiationPlugin;
+
+impl Plugin for RadiationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Radiation>()
+            .insert_resource(DecayTimer(Timer::new(
+                Duration::from_secs(1),
+                TimerMode::Repeating,
+            )))
+            .add_system(decay_system.run_if(simulation_running));
+    }
+}
+
+/// Decay is rolled on a fixed tick, so how much decays doesn't depend on the frame rate.
+#[derive(Resource)]
+struct DecayTimer(Timer);
+
+#[derive(Debug, Clone)]
+pub struct Decay {
+    pub half_life: Duration,
+    pub daughter: Element,
+    pub daughter_mass: Option<u16>,
+    /// Units of `Energy::Nuclear` released per decayed unit, alongside one unit of
+    /// `Energy::Ionization`.
+    pub energy: u32,
+}
+
+impl Decay {
+    fn new(half_life_secs: u64, daughter: Element, energy: u32) -> Self {
+        Self {
+            half_life: Duration::from_secs(half_life_secs),
+            daughter,
+            daughter_mass: None,
+            energy,
+        }
+    }
+
+    fn isotope(half_life_secs: u64, daughter: Element, daughter_mass: u16, energy: u32) -> Self {
+        Self {
+            daughter_mass: Some(daughter_mass),
+            ..Self::new(half_life_secs, daughter, energy)
+        }
+    }
+
+    /// Expected decays per second for `quantity` units.
+    pub fn activity(&self, quantity: u32) -> f32 {
+        quantity as f32 * std::f32::consts::LN_2 / self.half_life.as_secs_f32()
+    }
+
+    /// Expected decays of `quantity` units over `delta` seconds.
+    pub fn decayed(&self, quantity: u32, delta: f32) -> f32 {
+        quantity as f32 * (1. - 0.5_f32.powf(delta / self.half_life.as_secs_f32()))
+    }
+}
+
+// half-lives are compressed into game time, but keep the same ordering as the longest lived
+// isotope of each element
+lazy_static! {
+    pub static ref HALF_LIVES: HashMap<Element, Decay> = HashMap::from([
+        (Element::Technetium, Decay::new(7200, Element::Ruthenium, 1)),
+        (Element::Promethium, Decay::new(900, Element::Samarium, 1)),
+        (Element::Polonium, Decay::new(300, Element::Lead, 5)),
+        (Element::Astatine, Decay::new(30, Element::Bismuth, 5)),
+        (Element::Radon, Decay::new(60, Element::Polonium, 5)),
+        (Element::Francium, Decay::new(10, Element::Radium, 1)),
+        (Element::Radium, Decay::new(1200, Element::Radon, 4)),
+        (Element::Actinium, Decay::new(600, Element::Thorium, 1)),
+        (Element::Thorium, Decay::new(43200, Element::Radium, 4)),
+        (
+            Element::Protactinium,
+            Decay::new(3600, Element::Actinium, 5)
+        ),
+        (Element::Uranium, Decay::new(21600, Element::Thorium, 4)),
+        (
+            Element::Neptunium,
+            Decay::new(7200, Element::Protactinium, 5)
+        ),
+        (Element::Plutonium, Decay::new(3600, Element::Uranium, 5)),
+        (Element::Americium, Decay::new(1800, Element::Neptunium, 5)),
+        (Element::Curium, Decay::new(1800, Element::Plutonium, 6)),
+        (Element::Berkelium, Decay::new(900, Element::Americium, 6)),
+        (Element::Californium, Decay::new(600, Element::Curium, 6)),
+        (Element::Einsteinium, Decay::new(120, Element::Berkelium, 6)),
+        (Element::Fermium, Decay::new(60, Element::Californium, 7)),
+        (
+            Element::Mendelevium,
+            Decay::new(30, Element::Einsteinium, 7)
+        ),
+        (Element::Nobelium, Decay::new(20, Element::Fermium, 8)),
+        (Element::Lawrencium, Decay::new(15, Element::Mendelevium, 8)),
+        (Element::Rutherfordium, Decay::new(10, Element::Nobelium, 8)),
+        (Element::Dubnium, Decay::new(8, Element::Lawrencium, 8)),
+        (
+            Element::Seaborgium,
+            Decay::new(6, Element::Rutherfordium, 9)
+        ),
+        (Element::Bohrium, Decay::new(5, Element::Dubnium, 9)),
+        (Element::Hassium, Decay::new(4, Element::Seaborgium, 9)),
+        (Element::Meitnerium, Decay::new(3, Element::Bohrium, 9)),
+        (Element::Darmstadtium, Decay::new(3, Element::Hassium, 10)),
+        (Element::Roentgenium, Decay::new(2, Element::Meitnerium, 10)),
+        (
+            Element::Copernicium,
+            Decay::new(2, Element::Darmstadtium, 10)
+        ),
+        (Element::Nihonium, Decay::new(2, Element::Roentgenium, 10)),
+        (Element::Flerovium, Decay::new(1, Element::Copernicium, 10)),
+        (Element::Moscovium, Decay::new(1, Element::Nihonium, 10)),
+        (Element::Livermorium, Decay::new(1, Element::Flerovium, 10)),
+        (Element::Tennessine, Decay::new(1, Element::Moscovium, 10)),
+        (Element::Oganesson, Decay::new(1, Element::Livermorium, 10)),
+    ]);
+}
+
+// specific isotopes, which take precedence over the element wide table above. An isotope
+// missing from both is stable.
+lazy_static! {
+    pub static ref ISOTOPE_HALF_LIVES: HashMap<(Element, u16), Decay> = HashMap::from([
+        (
+            (Element::Hydrogen, 3),
+            Decay::isotope(600, Element::Helium, 3, 1)
+        ),
+        (
+            (Element::Carbon, 14),
+            Decay::isotope(3600, Element::Nitrogen, 14, 1)
+        ),
+        (
+            (Element::Potassium, 40),
+            Decay::isotope(14400, Element::Calcium, 40, 1)
+        ),
+        (
+            (Element::Cobalt, 60),
+            Decay::isotope(600, Element::Nickel, 60, 2)
+        ),
+        (
+            (Element::Strontium, 90),
+            Decay::isotope(1200, Element::Yttrium, 90, 1)
+        ),
+        (
+            (Element::Iodine, 131),
+            Decay::isotope(30, Element::Xenon, 131, 1)
+        ),
+        (
+            (Element::Cesium, 137),
+            Decay::isotope(1200, Element::Barium, 137, 1)
+        ),
+        (
+            (Element::Polonium, 210),
+            Decay::isotope(60, Element::Lead, 206, 5)
+        ),
+        (
+            (Element::Radon, 222),
+            Decay::isotope(20, Element::Polonium, 218, 5)
+        ),
+        (
+            (Element::Radium, 226),
+            Decay::isotope(2400, Element::Radon, 222, 4)
+        ),
+        (
+            (Element::Thorium, 232),
+            Decay::isotope(86400, Element::Radium, 228, 4)
+        ),
+        (
+            (Element::Uranium, 234),
+            Decay::isotope(7200, Element::Thorium, 230, 4)
+        ),
+        (
+            (Element::Uranium, 235),
+            Decay::isotope(14400, Element::Thorium, 231, 4)
+        ),
+        (
+            (Element::Uranium, 238),
+            Decay::isotope(43200, Element::Thorium, 234, 4)
+        ),
+        (
+            (Element::Neptunium, 237),
+            Decay::isotope(7200, Element::Protactinium, 233, 5)
+        ),
+        (
+            (Element::Plutonium, 238),
+            Decay::isotope(300, Element::Uranium, 234, 5)
+        ),
+        (
+            (Element::Plutonium, 239),
+            Decay::isotope(3600, Element::Uranium, 235, 5)
+        ),
+        (
+            (Element::Americium, 241),
+            Decay::isotope(900, Element::Neptunium, 237, 5)
+        ),
+    ]);
+}
+
+impl Element {
+    pub fn decay(&self, mass: Option<u16>) -> Option<&'static Decay> {
+        mass.and_then(|mass| ISOTOPE_HALF_LIVES.get(&(self.clone(), mass)))
+            .or_else(|| HALF_LIVES.get(self))
+    }
+}
+
+/// How much decaying material is nearby, in expected decays per second weighted by distance.
+#[derive(Component, Default, Reflect)]
+pub struct Radiation {
+    pub level: f32,
+}
+
+/// Decays every unstable stack in `inventory` by `delta` seconds, returning the activity of
+/// what is left along with what decayed and what it decayed into.
+fn decay_inventory(
+    inventory: &mut Inventory,
+    delta: f32,
+    limits: &StackLimits,
+) -> (f32, Vec<ItemStack>, Vec<ItemStack>) {
+    let mut parents: Vec<ItemStack> = vec![];
+    let mut decayed_stacks: Vec<ItemStack> = vec![];
+    let mut activity = 0.;
+
+    for stack in inventory.items.iter_mut() {
+        let ItemStackType::Element(element, state, mass) = &stack.item_type else {
+            continue;
+        };
+        let Some(decay) = element.decay(*mass) else {
+            continue;
+        };
+
+        // round the expected decays up or down at random so small stacks still decay
+        let expected = decay.decayed(stack.quantity, delta);
+        let mut decayed = expected.floor() as u32;
+        if rand::random::<f32>() < expected.fract() {
+            decayed += 1;
+        }
+        let decayed = decayed.min(stack.quantity);
+
+        stack.quantity -= decayed;
+        activity += decay.activity(stack.quantity);
+        if decayed > 0 {
+            parents.push(stack.item_type.to_item_stack(decayed));
+            decayed_stacks.push(
+                ItemStackType::Element(decay.daughter.clone(), state.clone(), decay.daughter_mass)
+                    .to_item_stack(decayed),
+            );
+            decayed_stacks.push(Energy::Nuclear.to_item_stack(decayed * decay.energy));
+            decayed_stacks.push(Energy::Ionization.to_item_stack(decayed));
+        }
+    }
+
+    inventory.items.retain(|item| item.quantity > 0);
+    for stack in decayed_stacks.iter() {
+        inventory.push(stack.clone(), limits);
+    }
+
+    (activity, parents, decayed_stacks)
+}
+
+fn report_decay(
+    entity: Entity,
+    parents: Vec<ItemStack>,
+    daughters: Vec<ItemStack>,
+    consumed: &mut EventWriter<ItemConsumed>,
+    produced: &mut EventWriter<ItemProduced>,
+) {
+    consumed.send_batch(
+        parents
+            .into_iter()
+            .map(|item| ItemConsumed { entity, item }),
+    );
+    produced.send_batch(
+        daughters
+            .into_iter()
+            .map(|item| ItemProduced { entity, item }),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decay_system(
+    mut input_query: Query<(Entity, &GlobalTransform, &mut components::Input)>,
+    mut output_query: Query<(Entity, &GlobalTransform, &mut Output)>,
+    mut player_query: Query<(Entity, &GlobalTransform, &mut PlayerInventory)>,
+    mut radiation_query: Query<(&GlobalTransform, &mut Radiation)>,
+    mut timer: ResMut<DecayTimer>,
+    time: Res<Time>,
+    limits: Res<StackLimits>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut produced: EventWriter<ItemProduced>,
+) {
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+    let delta = timer.0.duration().as_secs_f32() * timer.0.times_finished_this_tick() as f32;
+    let mut sources: Vec<(Vec3, f32)> = vec![];
+
+    // most inventories hold nothing unstable, so only mark the ones that decayed as changed
+    for (ent, trans, mut input) in input_query.iter_mut() {
+        let (activity, parents, daughters) = decay_inventory(
+            &mut input.bypass_change_detection().inventory,
+            delta,
+            &limits,
+        );
+        if !parents.is_empty() {
+            input.set_changed();
+        }
+        report_decay(ent, parents, daughters, &mut consumed, &mut produced);
+        if activity > 0. {
+            sources.push((trans.translation(), activity));
+        }
+    }
+    for (ent, trans, mut output) in output_query.iter_mut() {
+        let (activity, parents, daughters) = decay_inventory(
+            &mut output.bypass_change_detection().inventory,
+            delta,
+            &limits,
+        );
+        if !parents.is_empty() {
+            output.set_changed();
+        }
+        report_decay(ent, parents, daughters, &mut consumed, &mut produced);
+        if activity > 0. {
+            sources.push((trans.translation(), activity));
+        }
+    }
+    for (ent, trans, mut player) in player_query.iter_mut() {
+        let (activity, parents, daughters) = decay_inventory(
+            &mut player.bypass_change_detection().inventory,
+            delta,
+            &limits,
+        );
+        if !parents.is_empty() {
+            player.set_changed();
+        }
+        report_decay(ent, parents, daughters, &mut consumed, &mut produced);
+        if activity > 0. {
+            sources.push((trans.translation(), activity));
+        }
+    }
+
+    for (trans, mut radiation) in radiation_query.iter_mut() {
+        radiation.level = sources
+            .iter()
+            .map(|(position, activity)| {
+                activity / (1. + position.distance_squared(trans.translation()))
+            })
+            .sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn half_decays_each_half_life() {
+        let decay = Decay::new(60, Element::Lead, 1);
+
+        assert!(close(decay.decayed(1000, 60.), 500.));
+        assert!(close(decay.decayed(1000, 120.), 750.));
+        assert_eq!(decay.decayed(1000, 0.), 0.);
+        assert_eq!(decay.decayed(0, 60.), 0.);
+    }
+
+    #[test]
+    fn short_ticks_match_activity() {
+        let decay = Decay::new(3600, Element::Lead, 1);
+
+        // over a tick much shorter than the half-life, decays are close to the activity
+        assert!(close(decay.decayed(1000, 1.), decay.activity(1000)));
+    }
+
+    #[test]
+    fn ticks_add_up_to_the_same_decay() {
+        let decay = Decay::new(30, Element::Bismuth, 5);
+
+        let mut left = 1000.;
+        for _ in 0..30 {
+            left -= left * decay.decayed(1, 1.);
+        }
+        assert!(close(1000. - left, decay.decayed(1000, 30.)));
+    }
+}
@@ -1,18 +1,287 @@
 use std::time::Duration;
 
+use bevy::prelude::*;
+use enum_iterator::all;
 use lazy_static::lazy_static;
 
-use crate::materials::{Element, Reaction, State};
+use crate::materials::{Compound, Element, ElementFilter, Energy, ItemStack, Reaction, State};
+
+pub struct ReactionsPlugin;
+
+impl Plugin for ReactionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReactionRegistry>();
+    }
+}
 
 lazy_static! {
     pub static ref PROCESS_IRON_TO_GOLD: Reaction = Reaction {
+        name: "Iron to gold".to_string(),
         input: vec![Element::Iron.to_item_stack(State::Solid, 1)],
         output: vec![Element::Gold.to_item_stack(State::Solid, 1)],
         duration: Duration::from_secs(5),
     };
+    pub static ref PROCESS_BURN_HYDROGEN: Reaction = Reaction {
+        name: "Burn hydrogen".to_string(),
+        input: vec![
+            Element::Hydrogen.to_item_stack(State::Gas, 2),
+            Element::Oxygen.to_item_stack(State::Gas, 1),
+        ],
+        output: vec![
+            Compound::Water.to_item_stack(State::Liquid, 1),
+            Energy::Thermal.to_item_stack(10),
+        ],
+        duration: Duration::from_secs(1),
+    };
+}
+
+/// One side of a [`ReactionTemplate`].
+#[derive(Clone, Debug)]
+pub enum TemplateItem {
+    Fixed(ItemStack),
+    /// The element the template matched, in `state` or in the state it was matched in when
+    /// `None`.
+    Matched {
+        state: Option<State>,
+        quantity: u32,
+    },
+}
+
+impl TemplateItem {
+    fn expand(&self, element: &Element, state: &State) -> ItemStack {
+        match self {
+            TemplateItem::Fixed(stack) => stack.clone(),
+            TemplateItem::Matched {
+                state: to,
+                quantity,
+            } => element
+                .clone()
+                .to_item_stack(to.clone().unwrap_or(state.clone()), *quantity),
+        }
+    }
+}
+
+/// A reaction written once for every element passing all of `elements`, in each of `states`,
+/// or in every state when `states` is empty. No filters at all matches every element.
+#[derive(Clone, Debug)]
+pub struct ReactionTemplate {
+    pub name: String,
+    pub elements: Vec<ElementFilter>,
+    pub states: Vec<State>,
+    pub input: Vec<TemplateItem>,
+    pub output: Vec<TemplateItem>,
+    pub duration: Duration,
 }
 
-// todo: turn into file
-enum Reactions {
-    SolidIronToSolidGold(PROCESS_IRON_TO_GOLD),
+impl ReactionTemplate {
+    pub fn expand(&self) -> Vec<Reaction> {
+        let states = if self.states.is_empty() {
+            all::<State>().collect()
+        } else {
+            self.states.clone()
+        };
+
+        all::<Element>()
+            .filter(|element| self.elements.iter().all(|filter| filter.matches(element)))
+            .flat_map(|element| {
+                states.iter().map(move |state| Reaction {
+                    name: format!("{} {:?} ({:?})", self.name, element, state),
+                    input: self
+                        .input
+                        .iter()
+                        .map(|item| item.expand(&element, state))
+                        .collect(),
+                    output: self
+                        .output
+                        .iter()
+                        .map(|item| item.expand(&element, state))
+                        .collect(),
+                    duration: self.duration,
+                })
+            })
+            .collect()
+    }
+}
+
+lazy_static! {
+    pub static ref REACTION_TEMPLATES: Vec<ReactionTemplate> = vec![
+        ReactionTemplate {
+            name: "Quench".to_string(),
+            elements: vec![ElementFilter::Group(1), ElementFilter::Metal],
+            states: vec![State::Solid],
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Compound::Water.to_item_stack(State::Liquid, 1)),
+            ],
+            output: vec![
+                TemplateItem::Fixed(Element::Hydrogen.to_item_stack(State::Gas, 1)),
+                TemplateItem::Fixed(Energy::Thermal.to_item_stack(20)),
+            ],
+            duration: Duration::from_secs(1),
+        },
+        ReactionTemplate {
+            name: "Melt".to_string(),
+            elements: vec![ElementFilter::Metal],
+            states: vec![State::Solid],
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Thermal.to_item_stack(10)),
+            ],
+            output: vec![TemplateItem::Matched {
+                state: Some(State::Liquid),
+                quantity: 1
+            }],
+            duration: Duration::from_secs(3),
+        },
+        ReactionTemplate {
+            name: "Liquefy".to_string(),
+            elements: vec![ElementFilter::NonMetal],
+            states: vec![State::Gas],
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Mechanical.to_item_stack(10)),
+            ],
+            output: vec![
+                TemplateItem::Matched {
+                    state: Some(State::Liquid),
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Thermal.to_item_stack(5)),
+            ],
+            duration: Duration::from_secs(3),
+        },
+        ReactionTemplate {
+            name: "Fission".to_string(),
+            elements: vec![ElementFilter::Period(7), ElementFilter::Metal],
+            states: vec![State::Solid],
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Nuclear.to_item_stack(1)),
+            ],
+            output: vec![
+                TemplateItem::Fixed(Energy::Nuclear.to_item_stack(20)),
+                TemplateItem::Fixed(Energy::Ionization.to_item_stack(2)),
+            ],
+            duration: Duration::from_secs(5),
+        },
+        ReactionTemplate {
+            name: "Ionise".to_string(),
+            elements: vec![ElementFilter::Group(18)],
+            // already plasma would ionise into itself
+            states: vec![State::Solid, State::Liquid, State::Gas],
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Electric.to_item_stack(20)),
+            ],
+            output: vec![
+                TemplateItem::Matched {
+                    state: Some(State::Plasma),
+                    quantity: 1
+                },
+                TemplateItem::Fixed(Energy::Radiant.to_item_stack(5)),
+            ],
+            duration: Duration::from_secs(2),
+        },
+    ];
+}
+
+/// Every concrete reaction, with the templates expanded once at startup.
+#[derive(Resource)]
+pub struct ReactionRegistry {
+    pub reactions: Vec<Reaction>,
+}
+
+impl Default for ReactionRegistry {
+    fn default() -> Self {
+        let mut reactions = vec![PROCESS_IRON_TO_GOLD.clone(), PROCESS_BURN_HYDROGEN.clone()];
+        for template in REACTION_TEMPLATES.iter() {
+            reactions.extend(template.expand());
+        }
+        Self { reactions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(elements: Vec<ElementFilter>, states: Vec<State>) -> ReactionTemplate {
+        ReactionTemplate {
+            name: "Test".to_string(),
+            elements,
+            states,
+            input: vec![
+                TemplateItem::Matched {
+                    state: None,
+                    quantity: 2,
+                },
+                TemplateItem::Fixed(Energy::Thermal.to_item_stack(10)),
+            ],
+            output: vec![TemplateItem::Matched {
+                state: Some(State::Liquid),
+                quantity: 1,
+            }],
+            duration: Duration::from_secs(3),
+        }
+    }
+
+    #[test]
+    fn expand_fills_in_the_matched_element() {
+        let reactions = template(vec![ElementFilter::Period(1)], vec![State::Solid]).expand();
+
+        assert_eq!(reactions.len(), 2);
+        let hydrogen = &reactions[0];
+        assert_eq!(hydrogen.name, "Test Hydrogen (Solid)");
+        assert_eq!(
+            hydrogen.input,
+            vec![
+                Element::Hydrogen.to_item_stack(State::Solid, 2),
+                Energy::Thermal.to_item_stack(10),
+            ]
+        );
+        assert_eq!(
+            hydrogen.output,
+            vec![Element::Hydrogen.to_item_stack(State::Liquid, 1)]
+        );
+        assert_eq!(hydrogen.duration, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn expand_needs_every_filter_to_match() {
+        let reactions = template(
+            vec![ElementFilter::Group(1), ElementFilter::Metal],
+            vec![State::Solid],
+        )
+        .expand();
+
+        assert_eq!(reactions.len(), 6);
+        assert!(reactions
+            .iter()
+            .all(|reaction| !reaction.name.contains("Hydrogen")));
+    }
+
+    #[test]
+    fn expand_without_filters_covers_everything() {
+        let reactions = template(vec![], vec![]).expand();
+
+        assert_eq!(
+            reactions.len(),
+            all::<Element>().count() * all::<State>().count()
+        );
+    }
 }
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{HashMap, HashSet},
+};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+use serde::Deserialize;
+
+use crate::{
+    blocks::{set_status, BlockType},
+    components::{self, BlockStatus},
+    config::{RonAsset, RonAssetPlugin},
+    events::{BlockStalled, ItemConsumed, StallReason},
+    materials::{ItemStack, Reaction},
+    time_controls::simulation_running,
+    ui::not_typing,
+};
+
+pub struct ResearchPlugin;
+
+impl Plugin for ResearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Research>()
+            .init_resource::<ResearchWindow>()
+            .add_plugin(RonAssetPlugin::<TechTree>::new(TECH_TREE_PATH, "tech.ron"))
+            .add_system(lab_system.run_if(simulation_running))
+            .add_system(research_hotkey.run_if(not_typing))
+            .add_system(research_ui);
+    }
+}
+
+const TECH_TREE_PATH: &str = "config/default.tech.ron";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Technology {
+    pub id: String,
+    pub name: String,
+    /// Ids of the technologies that have to be researched first.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Items a lab has to be fed to finish it. With no cost it can't be researched at all and
+    /// is only unlocked by a quest.
+    pub cost: Vec<ItemStack>,
+    #[serde(default)]
+    pub blocks: Vec<BlockType>,
+    /// Reaction names, a template's name unlocks every reaction expanded from it.
+    #[serde(default)]
+    pub reactions: Vec<String>,
+}
+
+impl Technology {
+    fn unlocks_reaction(&self, reaction: &Reaction) -> bool {
+        self.reactions
+            .iter()
+            .any(|name| reaction.name == *name || reaction.name.starts_with(&format!("{} ", name)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "9c2e4a71-6b0f-4f3d-8e15-7a4d2c9b6e03"]
+pub struct TechTree {
+    pub technologies: Vec<Technology>,
+}
+
+impl RonAsset for TechTree {
+    type Target = Research;
+
+    fn apply(&self, research: &mut Research) {
+        research.technologies = self.technologies.clone();
+        research.loaded = true;
+    }
+}
+
+/// The loaded tree and how far through it the player is. Blocks and reactions no technology
+/// mentions are always available, once the tree has loaded and it is known which those are.
+#[derive(Resource, Default)]
+pub struct Research {
+    pub technologies: Vec<Technology>,
+    pub loaded: bool,
+    pub researched: HashSet<String>,
+    pub current: Option<String>,
+    /// Units delivered towards each item of a technology's cost, kept when switching to another.
+    pub progress: HashMap<String, Vec<u32>>,
+}
+
+impl Research {
+    pub fn get(&self, id: &str) -> Option<&Technology> {
+        self.technologies.iter().find(|tech| tech.id == id)
+    }
+
+    pub fn is_available(&self, tech: &Technology) -> bool {
+        !self.researched.contains(&tech.id)
+            && !tech.cost.is_empty()
+            && tech.requires.iter().all(|id| self.researched.contains(id))
+    }
+
+    /// Units delivered towards each item of `tech`'s cost.
+    pub fn progress(&self, tech: &Technology) -> Vec<u32> {
+        let mut progress = self.progress.get(&tech.id).cloned().unwrap_or_default();
+        progress.resize(tech.cost.len(), 0);
+        progress
+    }
+
+    pub fn is_block_unlocked(&self, block_type: &BlockType) -> bool {
+        if !self.loaded {
+            return false;
+        }
+        let mut gated = self
+            .technologies
+            .iter()
+            .filter(|tech| tech.blocks.contains(block_type))
+            .peekable();
+        gated.peek().is_none() || gated.any(|tech| self.researched.contains(&tech.id))
+    }
+
+    pub fn is_reaction_unlocked(&self, reaction: &Reaction) -> bool {
+        if !self.loaded {
+            return false;
+        }
+        let mut gated = self
+            .technologies
+            .iter()
+            .filter(|tech| tech.unlocks_reaction(reaction))
+            .peekable();
+        gated.peek().is_none() || gated.any(|tech| self.researched.contains(&tech.id))
+    }
+
+    pub fn start(&mut self, id: &str) {
+        if self.get(id).is_none() {
+            return;
+        }
+        self.current = Some(id.to_string());
+    }
+
+    /// Marks `id` researched, dropping it if it was being researched.
+    pub fn unlock(&mut self, id: &str) {
+        self.researched.insert(id.to_string());
+        self.progress.remove(id);
+        if self.current.as_deref() == Some(id) {
+            self.current = None;
+        }
+    }
+}
+
+/// Feeds the items in its input into the current research.
+#[derive(Component)]
+pub struct Lab {
+    pub timer: Timer,
+}
+
+impl Default for Lab {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(Duration::from_millis(1000), TimerMode::Repeating),
+        }
+    }
+}
+
+fn lab_system(
+    mut research: ResMut<Research>,
+    mut lab_query: Query<(Entity, &mut Lab, &mut components::Input, &mut BlockStatus)>,
+    time: Res<Time>,
+    mut consumed: EventWriter<ItemConsumed>,
+    mut stalled: EventWriter<BlockStalled>,
+) {
+    let Some(tech) = research
+        .current
+        .clone()
+        .and_then(|id| research.get(&id).cloned())
+    else {
+        for (ent, _, _, mut status) in lab_query.iter_mut() {
+            set_status(ent, &mut status, Some(StallReason::Idle), &mut stalled);
+        }
+        return;
+    };
+
+    // a reload can change the cost, so always go by its current length
+    let mut progress = research.progress(&tech);
+    for (ent, mut lab, mut input, mut status) in lab_query.iter_mut() {
+        lab.timer.tick(time.delta());
+        if !lab.timer.just_finished() {
+            continue;
+        }
+        let mut missing = None;
+        let mut took = false;
+        for (item, delivered) in tech.cost.iter().zip(progress.iter_mut()) {
+            let needed = item.quantity.saturating_sub(*delivered);
+            let taken = needed.min(input.inventory.quantity_matching(&item.item_type));
+            if taken == 0 {
+                if needed > 0 && missing.is_none() {
+                    missing = Some(item.item_type.clone());
+                }
+                continue;
+            }
+            let used = item.item_type.to_item_stack(taken);
+            input.inventory.remove_matching(&used);
+            consumed.send(ItemConsumed {
+                entity: ent,
+                item: used,
+            });
+            *delivered += taken;
+            took = true;
+        }
+        let stall = if took {
+            None
+        } else {
+            missing.map(StallReason::MissingInput)
+        };
+        set_status(ent, &mut status, stall, &mut stalled);
+    }
+
+    let done = tech
+        .cost
+        .iter()
+        .zip(progress.iter())
+        .all(|(item, delivered)| *delivered >= item.quantity);
+    if done {
+        info!("Researched {}", tech.name);
+        research.unlock(&tech.id);
+    } else if research.progress.get(&tech.id) != Some(&progress) {
+        research.progress.insert(tech.id, progress);
+    }
+}
+
+#[derive(Resource, Default)]
+struct ResearchWindow {
+    open: bool,
+}
+
+fn research_hotkey(keys: Res<Input<KeyCode>>, mut window: ResMut<ResearchWindow>) {
+    if keys.just_pressed(KeyCode::T) {
+        window.open = !window.open;
+    }
+}
+
+fn research_ui(
+    mut egui_ctx: EguiContexts,
+    mut window: ResMut<ResearchWindow>,
+    mut research: ResMut<Research>,
+) {
+    if !window.open {
+        return;
+    }
+    egui::Window::new("Research")
+        .open(&mut window.open)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            if !research.loaded {
+                ui.label("Loading technologies...");
+                return;
+            }
+            if let Some(tech) = research.current.clone().and_then(|id| research.get(&id)) {
+                ui.heading(format!("Researching {}", tech.name));
+                for (item, delivered) in tech.cost.iter().zip(research.progress(tech).iter()) {
+                    ui.add(
+                        egui::ProgressBar::new(*delivered as f32 / item.quantity as f32).text(
+                            format!("{} {}/{}", item.item_type, delivered, item.quantity),
+                        ),
+                    );
+                }
+                ui.separator();
+            }
+
+            let mut start = None;
+            for tech in research.technologies.iter() {
+                ui.horizontal(|ui| {
+                    if research.researched.contains(&tech.id) {
+                        ui.label(format!("{} (done)", tech.name));
+                    } else if tech.cost.is_empty() {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            format!("{} (unlocked by a quest)", tech.name),
+                        );
+                    } else if research.is_available(tech) {
+                        ui.label(&tech.name);
+                        if research.current.as_ref() != Some(&tech.id)
+                            && ui.button("Research").clicked()
+                        {
+                            start = Some(tech.id.clone());
+                        }
+                    } else {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            format!("{} (needs {})", tech.name, tech.requires.join(", ")),
+                        );
+                    }
+                });
+            }
+            if let Some(id) = start {
+                research.start(&id);
+            }
+        });
+}
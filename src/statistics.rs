@@ -0,0 +1,499 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Write},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_inspector_egui::bevy_egui::{
+    egui::{
+        self,
+        plot::{Legend, Line, Plot, PlotPoints},
+    },
+    EguiContexts,
+};
+use enum_iterator::Sequence;
+
+use crate::{
+    blocks::block_name,
+    events::{ItemConsumed, ItemProduced},
+    materials::ItemStackType,
+    player::enum_dropdown,
+    ui::not_typing,
+};
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Statistics>()
+            .init_resource::<StatisticsWindow>()
+            .add_system(record_statistics)
+            .add_system(statistics_hotkey.run_if(not_typing))
+            .add_system(statistics_ui);
+    }
+}
+
+/// Where exported series are written, relative to the working directory.
+const EXPORT_PATH: &str = "production_statistics.csv";
+
+/// Points on the graph, whatever the period.
+const GRAPH_POINTS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Sequence)]
+pub enum Period {
+    #[default]
+    Minute,
+    TenMinutes,
+    Hour,
+}
+
+impl Period {
+    pub fn seconds(&self) -> usize {
+        match self {
+            Period::Minute => 60,
+            Period::TenMinutes => 600,
+            Period::Hour => 3600,
+        }
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Everything produced and consumed during one second.
+#[derive(Default)]
+struct Sample {
+    produced: HashMap<ItemStackType, u32>,
+    consumed: HashMap<ItemStackType, u32>,
+    /// Units produced and consumed by each block.
+    blocks: HashMap<Entity, (u32, u32)>,
+}
+
+#[derive(Resource, Default)]
+pub struct Statistics {
+    /// One per second, newest last, as many as the longest period.
+    samples: VecDeque<Sample>,
+    /// Whole seconds since startup when the newest sample began.
+    second: u64,
+}
+
+/// Units per minute over a period.
+#[derive(Debug, Clone)]
+pub struct Rates<T> {
+    pub key: T,
+    pub produced: f32,
+    pub consumed: f32,
+}
+
+impl<T> Rates<T> {
+    pub fn net(&self) -> f32 {
+        self.produced - self.consumed
+    }
+}
+
+fn count(items: &HashMap<ItemStackType, u32>, item: Option<&ItemStackType>) -> u32 {
+    match item {
+        Some(item) => items.get(item).copied().unwrap_or(0),
+        None => items.values().sum(),
+    }
+}
+
+impl Statistics {
+    fn period(&self, period: Period) -> impl Iterator<Item = &Sample> {
+        self.samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(period.seconds()))
+    }
+
+    pub fn item_rates(&self, period: Period) -> Vec<Rates<ItemStackType>> {
+        let mut totals: HashMap<ItemStackType, (u32, u32)> = HashMap::new();
+        for sample in self.period(period) {
+            for (item, quantity) in sample.produced.iter() {
+                totals.entry(item.clone()).or_default().0 += quantity;
+            }
+            for (item, quantity) in sample.consumed.iter() {
+                totals.entry(item.clone()).or_default().1 += quantity;
+            }
+        }
+        self.to_rates(totals, period)
+    }
+
+    pub fn block_rates(&self, period: Period) -> Vec<Rates<Entity>> {
+        let mut totals: HashMap<Entity, (u32, u32)> = HashMap::new();
+        for sample in self.period(period) {
+            for (entity, (produced, consumed)) in sample.blocks.iter() {
+                let total = totals.entry(*entity).or_default();
+                total.0 += produced;
+                total.1 += consumed;
+            }
+        }
+        self.to_rates(totals, period)
+    }
+
+    /// Produced and consumed per minute of `item`, or of everything, as [`GRAPH_POINTS`] points
+    /// oldest first. x is in minutes before now.
+    pub fn series(
+        &self,
+        item: Option<&ItemStackType>,
+        period: Period,
+    ) -> (Vec<[f64; 2]>, Vec<[f64; 2]>) {
+        let bucket = period.seconds() / GRAPH_POINTS;
+        let samples = self.period(period).collect::<Vec<_>>();
+        // seconds before the game started, with nothing in them
+        let missing = period.seconds() - samples.len();
+
+        let mut produced = vec![];
+        let mut consumed = vec![];
+        for point in 0..GRAPH_POINTS {
+            let (mut made, mut used) = (0, 0);
+            for second in point * bucket..(point + 1) * bucket {
+                let Some(sample) = second.checked_sub(missing).and_then(|i| samples.get(i)) else {
+                    continue;
+                };
+                made += count(&sample.produced, item);
+                used += count(&sample.consumed, item);
+            }
+            let x = -(((GRAPH_POINTS - point) * bucket) as f64) / 60.;
+            let scale = 60. / bucket as f64;
+            produced.push([x, made as f64 * scale]);
+            consumed.push([x, used as f64 * scale]);
+        }
+        (produced, consumed)
+    }
+
+    /// Every second of the period, one row per item seen in it.
+    pub fn to_csv(&self, period: Period) -> String {
+        let mut csv = "seconds_ago,item,produced,consumed\n".to_string();
+        let samples = self.period(period).collect::<Vec<_>>();
+        for (i, sample) in samples.iter().enumerate() {
+            let mut items = sample.produced.keys().collect::<Vec<_>>();
+            items.extend(
+                sample
+                    .consumed
+                    .keys()
+                    .filter(|item| !sample.produced.contains_key(*item)),
+            );
+            for item in items {
+                let _ = writeln!(
+                    csv,
+                    "{},\"{}\",{},{}",
+                    samples.len() - 1 - i,
+                    item,
+                    count(&sample.produced, Some(item)),
+                    count(&sample.consumed, Some(item)),
+                );
+            }
+        }
+        csv
+    }
+
+    /// Averages over the seconds recorded so far, so early rates aren't diluted by the part of
+    /// the period before the game started.
+    fn to_rates<T>(&self, totals: HashMap<T, (u32, u32)>, period: Period) -> Vec<Rates<T>> {
+        let seconds = self.samples.len().min(period.seconds()).max(1);
+        let minutes = seconds as f32 / 60.;
+        totals
+            .into_iter()
+            .map(|(key, (produced, consumed))| Rates {
+                key,
+                produced: produced as f32 / minutes,
+                consumed: consumed as f32 / minutes,
+            })
+            .collect()
+    }
+
+    fn current(&mut self, now: u64) -> &mut Sample {
+        if self.samples.is_empty() {
+            self.second = now;
+            self.samples.push_back(Sample::default());
+        }
+        while self.second < now {
+            self.second += 1;
+            self.samples.push_back(Sample::default());
+            if self.samples.len() > Period::Hour.seconds() {
+                self.samples.pop_front();
+            }
+        }
+        self.samples.back_mut().unwrap()
+    }
+}
+
+fn record_statistics(
+    mut statistics: ResMut<Statistics>,
+    mut produced: EventReader<ItemProduced>,
+    mut consumed: EventReader<ItemConsumed>,
+    time: Res<Time>,
+) {
+    let sample = statistics.current(time.elapsed_seconds() as u64);
+    for event in produced.iter() {
+        *sample
+            .produced
+            .entry(event.item.item_type.clone())
+            .or_default() += event.item.quantity;
+        sample.blocks.entry(event.entity).or_default().0 += event.item.quantity;
+    }
+    for event in consumed.iter() {
+        *sample
+            .consumed
+            .entry(event.item.item_type.clone())
+            .or_default() += event.item.quantity;
+        sample.blocks.entry(event.entity).or_default().1 += event.item.quantity;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Name,
+    Produced,
+    Consumed,
+    Net,
+}
+
+#[derive(Resource)]
+struct StatisticsWindow {
+    open: bool,
+    period: Period,
+    sort: SortBy,
+    descending: bool,
+    /// Item to graph, everything when none.
+    selected: Option<ItemStackType>,
+}
+
+impl Default for StatisticsWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            period: Period::default(),
+            sort: SortBy::Produced,
+            descending: true,
+            selected: None,
+        }
+    }
+}
+
+impl StatisticsWindow {
+    fn sort<T>(&self, rates: &mut [Rates<T>], name: impl Fn(&T) -> String) {
+        rates.sort_by(|a, b| {
+            let ordering = match self.sort {
+                SortBy::Name => name(&a.key).cmp(&name(&b.key)),
+                SortBy::Produced => a.produced.total_cmp(&b.produced),
+                SortBy::Consumed => a.consumed.total_cmp(&b.consumed),
+                SortBy::Net => a.net().total_cmp(&b.net()),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// A column heading that sorts by its column when clicked, or flips the order if it already
+    /// does.
+    fn header(&mut self, ui: &mut egui::Ui, label: &str, sort: SortBy) {
+        let text = match (self.sort == sort, self.descending) {
+            (true, true) => format!("{} v", label),
+            (true, false) => format!("{} ^", label),
+            (false, _) => label.to_string(),
+        };
+        if ui.button(text).clicked() {
+            if self.sort == sort {
+                self.descending = !self.descending;
+            } else {
+                self.sort = sort;
+                self.descending = sort != SortBy::Name;
+            }
+        }
+    }
+}
+
+fn statistics_hotkey(keys: Res<Input<KeyCode>>, mut window: ResMut<StatisticsWindow>) {
+    if keys.just_pressed(KeyCode::S) {
+        window.open = !window.open;
+    }
+}
+
+fn statistics_ui(
+    mut egui_ctx: EguiContexts,
+    mut window: ResMut<StatisticsWindow>,
+    statistics: Res<Statistics>,
+    name_query: Query<&Name>,
+) {
+    if !window.open {
+        return;
+    }
+    let window = &mut *window;
+    let mut open = window.open;
+
+    egui::Window::new("Production Statistics")
+        .open(&mut open)
+        .default_width(450.)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                enum_dropdown::<Period>(
+                    ui,
+                    "statistics-period".to_string(),
+                    "Period",
+                    &mut window.period,
+                );
+                if ui.button("Export CSV").clicked() {
+                    match std::fs::write(EXPORT_PATH, statistics.to_csv(window.period)) {
+                        Ok(()) => info!("Exported production statistics to {}", EXPORT_PATH),
+                        Err(err) => error!("Could not write {}: {}", EXPORT_PATH, err),
+                    }
+                }
+            });
+
+            let (produced, consumed) = statistics.series(window.selected.as_ref(), window.period);
+            ui.label(match &window.selected {
+                Some(item) => format!("{} per minute", item),
+                None => "Everything per minute".to_string(),
+            });
+            Plot::new("statistics_plot")
+                .height(150.)
+                .legend(Legend::default())
+                .allow_drag(false)
+                .allow_zoom(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(produced)).name("Produced"));
+                    plot_ui.line(Line::new(PlotPoints::new(consumed)).name("Consumed"));
+                });
+            ui.separator();
+
+            let mut items = statistics.item_rates(window.period);
+            window.sort(&mut items, |item| item.to_string());
+            egui::CollapsingHeader::new("Items")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("statistics_items")
+                        .max_height(200.)
+                        .show(ui, |ui| {
+                            egui::Grid::new("statistics_items_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    window.header(ui, "Item", SortBy::Name);
+                                    window.header(ui, "Produced/min", SortBy::Produced);
+                                    window.header(ui, "Consumed/min", SortBy::Consumed);
+                                    window.header(ui, "Net/min", SortBy::Net);
+                                    ui.end_row();
+                                    for rates in items.iter() {
+                                        let selected = window.selected.as_ref() == Some(&rates.key);
+                                        if ui
+                                            .selectable_label(selected, rates.key.to_string())
+                                            .clicked()
+                                        {
+                                            window.selected =
+                                                (!selected).then(|| rates.key.clone());
+                                        }
+                                        ui.label(format!("{:.1}", rates.produced));
+                                        ui.label(format!("{:.1}", rates.consumed));
+                                        ui.label(format!("{:.1}", rates.net()));
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+
+            let name = |entity: &Entity| block_name(*entity, &name_query);
+            let mut blocks = statistics.block_rates(window.period);
+            window.sort(&mut blocks, name);
+            egui::CollapsingHeader::new("Blocks").show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("statistics_blocks")
+                    .max_height(200.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("statistics_blocks_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                window.header(ui, "Block", SortBy::Name);
+                                window.header(ui, "Produced/min", SortBy::Produced);
+                                window.header(ui, "Consumed/min", SortBy::Consumed);
+                                window.header(ui, "Net/min", SortBy::Net);
+                                ui.end_row();
+                                for rates in blocks.iter() {
+                                    ui.label(name(&rates.key));
+                                    ui.label(format!("{:.1}", rates.produced));
+                                    ui.label(format!("{:.1}", rates.consumed));
+                                    ui.label(format!("{:.1}", rates.net()));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+        });
+    window.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use enum_iterator::all;
+
+    use super::*;
+    use crate::materials::{Element, State};
+
+    fn iron() -> ItemStackType {
+        ItemStackType::Element(Element::Iron, State::Solid, None)
+    }
+
+    /// Half a minute of a block making 2 iron a second and using 1.
+    fn half_a_minute() -> Statistics {
+        let mut statistics = Statistics::default();
+        for second in 0..30 {
+            let sample = statistics.current(second);
+            sample.produced.insert(iron(), 2);
+            sample.consumed.insert(iron(), 1);
+            sample.blocks.insert(Entity::from_raw(0), (2, 1));
+        }
+        statistics
+    }
+
+    #[test]
+    fn rates_average_over_recorded_seconds() {
+        let statistics = half_a_minute();
+
+        for period in all::<Period>() {
+            let rates = statistics.item_rates(period);
+            assert_eq!(rates.len(), 1);
+            assert_eq!(rates[0].key, iron());
+            assert_eq!(rates[0].produced, 120.);
+            assert_eq!(rates[0].consumed, 60.);
+            assert_eq!(rates[0].net(), 60.);
+        }
+
+        let blocks = statistics.block_rates(Period::Minute);
+        assert_eq!(blocks[0].key, Entity::from_raw(0));
+        assert_eq!(blocks[0].produced, 120.);
+    }
+
+    #[test]
+    fn series_is_empty_before_the_game_started() {
+        let statistics = half_a_minute();
+
+        let (produced, consumed) = statistics.series(Some(&iron()), Period::Minute);
+        assert_eq!(produced.len(), GRAPH_POINTS);
+        assert_eq!(produced[0], [-1., 0.]);
+        assert_eq!(produced[29][1], 0.);
+        assert_eq!(produced[30][1], 120.);
+        assert_eq!(produced[59], [-1. / 60., 120.]);
+        assert_eq!(consumed[59][1], 60.);
+
+        // ten minutes of graph puts ten seconds in each point
+        let (produced, _) = statistics.series(None, Period::TenMinutes);
+        assert_eq!(produced[56][1], 0.);
+        assert_eq!(produced[57][1], 120.);
+    }
+
+    #[test]
+    fn samples_are_kept_for_an_hour() {
+        let mut statistics = Statistics::default();
+        statistics.current(0).produced.insert(iron(), 1);
+        statistics.current(Period::Hour.seconds() as u64);
+
+        assert_eq!(statistics.samples.len(), Period::Hour.seconds());
+        assert!(statistics.item_rates(Period::Hour).is_empty());
+    }
+}
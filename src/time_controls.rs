@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::egui;
+
+use crate::ui::not_typing;
+
+pub struct TimeControlsPlugin;
+
+impl Plugin for TimeControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeControls>()
+            .add_system(time_controls_hotkeys.run_if(not_typing))
+            .add_system(apply_time_controls.after(time_controls_hotkeys));
+    }
+}
+
+/// Multipliers the simulation can run at.
+pub const SPEEDS: [f32; 5] = [0.5, 1., 2., 4., 8.];
+
+/// Pause and speed are applied to [`Time`] itself, so every block system follows them, while the
+/// camera and the UI, which don't read it, carry on as normal.
+#[derive(Resource)]
+pub struct TimeControls {
+    /// Index into [`SPEEDS`].
+    pub speed: usize,
+    /// Unpaused for a single frame, to be paused again once time has moved.
+    stepping: bool,
+}
+
+impl Default for TimeControls {
+    fn default() -> Self {
+        Self {
+            speed: 1,
+            stepping: false,
+        }
+    }
+}
+
+impl TimeControls {
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn toggle_pause(&mut self, time: &mut Time) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+        self.stepping = false;
+    }
+
+    /// Runs the simulation for one frame, only while paused.
+    pub fn step(&mut self, time: &mut Time) {
+        if time.is_paused() {
+            time.unpause();
+            self.stepping = true;
+        }
+    }
+}
+
+/// Run condition for the systems that advance the simulation, so they stop entirely while paused
+/// rather than each having to notice a zero delta.
+pub fn simulation_running(time: Res<Time>) -> bool {
+    !time.is_paused()
+}
+
+fn time_controls_hotkeys(
+    keys: Res<Input<KeyCode>>,
+    mut controls: ResMut<TimeControls>,
+    mut time: ResMut<Time>,
+) {
+    if keys.just_pressed(KeyCode::P) {
+        controls.toggle_pause(&mut time);
+    }
+    if keys.just_pressed(KeyCode::Minus) {
+        controls.slower();
+    }
+    if keys.just_pressed(KeyCode::Equals) {
+        controls.faster();
+    }
+    if keys.just_pressed(KeyCode::Period) {
+        controls.step(&mut time);
+    }
+}
+
+fn apply_time_controls(mut controls: ResMut<TimeControls>, mut time: ResMut<Time>) {
+    if time.relative_speed() != controls.speed() {
+        time.set_relative_speed(controls.speed());
+    }
+    // the frame time was unpaused for has now run
+    if controls.stepping && !time.delta().is_zero() {
+        time.pause();
+        controls.stepping = false;
+    }
+}
+
+/// Pause, step and speed buttons for the settings panel.
+pub fn time_controls_ui(ui: &mut egui::Ui, controls: &mut TimeControls, time: &mut Time) {
+    ui.horizontal(|ui| {
+        let label = if time.is_paused() {
+            "Resume (P)"
+        } else {
+            "Pause (P)"
+        };
+        if ui.button(label).clicked() {
+            controls.toggle_pause(time);
+        }
+        if ui
+            .add_enabled(time.is_paused(), egui::Button::new("Step (.)"))
+            .clicked()
+        {
+            controls.step(time);
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Speed (- =)");
+        for (i, speed) in SPEEDS.iter().enumerate() {
+            ui.selectable_value(&mut controls.speed, i, format!("{}x", speed));
+        }
+    });
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::EguiContexts;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Typing>()
+            .add_system(track_typing.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+/// Whether a text box has the keyboard, kept here because run conditions can't borrow egui.
+#[derive(Resource, Default)]
+pub struct Typing(bool);
+
+fn track_typing(mut egui_ctx: EguiContexts, mut typing: ResMut<Typing>) {
+    typing.0 = egui_ctx.ctx_mut().wants_keyboard_input();
+}
+
+/// Run condition for hotkeys, so they don't fire while typing into a search box.
+pub fn not_typing(typing: Res<Typing>) -> bool {
+    !typing.0
+}